@@ -0,0 +1,199 @@
+use std::path::Path;
+
+use chrono::{Days, NaiveDate};
+
+use crate::{
+    datetime::week_bounds,
+    milestone::Milestone,
+    task::{Task, TaskStatus},
+    Error, Result,
+};
+
+const SVG_WIDTH: u32 = 640;
+const SVG_HEIGHT: u32 = 320;
+const SVG_MARGIN: u32 = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChartKind {
+    Burndown,
+    Throughput,
+}
+
+/// One value per day for burndowns, per week (keyed by its monday) for throughput
+pub struct ChartSeries {
+    pub kind: ChartKind,
+    pub title: String,
+    pub points: Vec<(NaiveDate, u32)>,
+}
+
+impl ChartSeries {
+    pub fn headers(&self) -> [&'static str; 2] {
+        match self.kind {
+            ChartKind::Burndown => ["date", "remaining"],
+            ChartKind::Throughput => ["week", "completed"],
+        }
+    }
+
+    pub fn max(&self) -> u32 {
+        self.points
+            .iter()
+            .map(|(_, value)| *value)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = self.headers().join(",") + "\n";
+        self.points
+            .iter()
+            .for_each(|(date, value)| csv.push_str(&format!("{},{}\n", date, value)));
+        csv
+    }
+
+    pub fn to_svg(&self) -> String {
+        let plot_width = (SVG_WIDTH - 2 * SVG_MARGIN) as f64;
+        let plot_height = (SVG_HEIGHT - 2 * SVG_MARGIN) as f64;
+        let bottom = (SVG_HEIGHT - SVG_MARGIN) as f64;
+        let max = self.max().max(1) as f64;
+        let step = plot_width / self.points.len().max(1) as f64;
+        let y = |value: u32| bottom - value as f64 / max * plot_height;
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{SVG_WIDTH}" height="{SVG_HEIGHT}" font-family="sans-serif" font-size="12">
+<text x="{SVG_MARGIN}" y="24" font-size="16">{}</text>
+<line x1="{SVG_MARGIN}" y1="{bottom}" x2="{}" y2="{bottom}" stroke="black"/>
+<line x1="{SVG_MARGIN}" y1="{SVG_MARGIN}" x2="{SVG_MARGIN}" y2="{bottom}" stroke="black"/>
+<text x="{}" y="{}" text-anchor="end">{}</text>
+"#,
+            escape_xml(&self.title),
+            SVG_WIDTH - SVG_MARGIN,
+            SVG_MARGIN - 4,
+            SVG_MARGIN + 4,
+            self.max()
+        );
+        match self.kind {
+            ChartKind::Burndown => {
+                let points: Vec<String> = self
+                    .points
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, value))| {
+                        format!(
+                            "{:.1},{:.1}",
+                            SVG_MARGIN as f64 + (i as f64 + 0.5) * step,
+                            y(*value)
+                        )
+                    })
+                    .collect();
+                svg.push_str(&format!(
+                    "<polyline points=\"{}\" fill=\"none\" stroke=\"steelblue\" stroke-width=\"2\"/>\n",
+                    points.join(" ")
+                ));
+            }
+            ChartKind::Throughput => self.points.iter().enumerate().for_each(|(i, (_, value))| {
+                svg.push_str(&format!(
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"steelblue\"/>\n",
+                    SVG_MARGIN as f64 + i as f64 * step + step * 0.1,
+                    y(*value),
+                    step * 0.8,
+                    bottom - y(*value)
+                ))
+            }),
+        }
+        if let (Some((first, _)), Some((last, _))) = (self.points.first(), self.points.last()) {
+            svg.push_str(&format!(
+                "<text x=\"{SVG_MARGIN}\" y=\"{}\">{}</text>\n<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\n",
+                bottom + 16.0,
+                first,
+                SVG_WIDTH - SVG_MARGIN,
+                bottom + 16.0,
+                last
+            ));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Open tasks of the milestone at the end of each day, from the day the
+/// milestone or its first task was created until `today`. Cancelled tasks are left out
+pub fn burndown(milestone: &Milestone, tasks: &[Task], today: NaiveDate) -> ChartSeries {
+    let tasks: Vec<&Task> = tasks
+        .iter()
+        .filter(|task| task.milestone.as_ref() == Some(&milestone.name))
+        .filter(|task| task.status != TaskStatus::Cancelled)
+        .collect();
+    let start = tasks
+        .iter()
+        .map(|task| task.created_at.date_naive())
+        .chain([milestone.created_at.date_naive()])
+        .min()
+        .unwrap_or(today)
+        .min(today);
+    let points = start
+        .iter_days()
+        .take_while(|day| *day <= today)
+        .map(|day| {
+            let remaining = tasks
+                .iter()
+                .filter(|task| task.created_at.date_naive() <= day)
+                .filter(|task| {
+                    task.completed_at()
+                        .is_none_or(|completed_at| completed_at.date_naive() > day)
+                })
+                .count();
+            (day, remaining as u32)
+        })
+        .collect();
+    ChartSeries {
+        kind: ChartKind::Burndown,
+        title: format!("Burndown for {}", milestone.name),
+        points,
+    }
+}
+
+/// Tasks completed in each of the last `weeks` weeks, the current one included
+pub fn throughput(tasks: &[Task], weeks: u32, today: NaiveDate) -> ChartSeries {
+    let (this_monday, _) = week_bounds(today);
+    let points = (0..weeks)
+        .rev()
+        .map(|weeks_ago| {
+            let monday = this_monday - Days::new(7 * weeks_ago as u64);
+            let sunday = monday + Days::new(6);
+            let completed = tasks
+                .iter()
+                .filter_map(|task| task.completed_at())
+                .map(|completed_at| completed_at.date_naive())
+                .filter(|day| monday <= *day && *day <= sunday)
+                .count();
+            (monday, completed as u32)
+        })
+        .collect();
+    ChartSeries {
+        kind: ChartKind::Throughput,
+        title: format!("Throughput over the last {} weeks", weeks),
+        points,
+    }
+}
+
+/// Writes the series as CSV or SVG depending on the file extension
+pub fn export_chart(series: &ChartSeries, path: &Path) -> Result<()> {
+    let content = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => series.to_csv(),
+        Some("svg") => series.to_svg(),
+        _ => {
+            return Err(Error::UnsupportedExportFormat {
+                path: path.display().to_string(),
+            })
+        }
+    };
+    std::fs::write(path, content).map_err(|_| Error::FailedToExportChart {
+        path: path.display().to_string(),
+    })
+}
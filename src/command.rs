@@ -1,37 +1,221 @@
+use std::{collections::HashSet, path::PathBuf, str::FromStr};
+
+use chrono::{Local, NaiveDate, TimeDelta};
+
 use crate::{
-    task::{TaskDescription, TaskId, TaskStatus},
+    datetime::{parse_date, parse_duration, parse_past_date, week_bounds},
+    filter::Filter,
+    heatmap::HeatmapMetric,
+    recurrence::RecurrenceRule,
+    sort::SortOrder,
+    standup::StandupFormat,
+    task::{clean_body, Estimate, TaskDescription, TaskId, TaskStatus},
+    timesheet::TimesheetGrouping,
     Error, Result,
 };
 
+const DEFAULT_HISTORY_WEEKS: u32 = 12;
+/// Ten years, far enough back for any history while keeping dates in range
+const MAX_HISTORY_WEEKS: u32 = 520;
+
 #[derive(Debug)]
 pub struct CommandAdd {
     pub description: TaskDescription,
+    pub due: Option<NaiveDate>,
+    pub recur: Option<RecurrenceRule>,
+    pub milestone: Option<String>,
+    pub body: Option<String>,
+}
+
+/// The tasks a command acts on: a single id, a list of ids and ranges such
+/// as `1,4,7-12`, or every task matching `--where`
+#[derive(Debug)]
+pub enum TaskSelector {
+    Id(TaskId),
+    Ids(Vec<TaskId>),
+    Filter(Filter),
 }
 
 #[derive(Debug)]
 pub struct CommandUpdate {
-    pub id: TaskId,
+    pub selector: TaskSelector,
     pub description: TaskDescription,
 }
 
 #[derive(Debug)]
 pub struct CommandDelete {
+    pub selector: TaskSelector,
+}
+
+#[derive(Debug)]
+pub struct CommandSetStatus {
+    pub selector: TaskSelector,
+    pub status: TaskStatus,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DependencyFilter {
+    Blocked,
+    Ready,
+}
+
+#[derive(Debug)]
+pub struct CommandList {
+    pub status: Option<TaskStatus>,
+    pub dependency: Option<DependencyFilter>,
+    pub filter: Option<Filter>,
+    pub sort: Option<SortOrder>,
+    pub reverse: bool,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct CommandDepend {
     pub id: TaskId,
+    pub on: TaskId,
 }
 
 #[derive(Debug)]
-pub struct CommandMarkInProgress {
+pub struct CommandShow {
     pub id: TaskId,
+    pub json: bool,
 }
 
 #[derive(Debug)]
-pub struct CommandMarkDone {
+pub struct CommandAnnotate {
     pub id: TaskId,
+    pub text: String,
 }
 
 #[derive(Debug)]
-pub struct CommandList {
-    pub status: Option<TaskStatus>,
+pub struct CommandCheckAdd {
+    pub id: TaskId,
+    pub text: String,
+}
+
+#[derive(Debug)]
+pub struct CommandCheckDone {
+    pub id: TaskId,
+    /// Counting from 1
+    pub number: usize,
+}
+
+#[derive(Debug)]
+pub struct CommandEdit {
+    pub id: TaskId,
+}
+
+#[derive(Debug)]
+pub struct CommandStart {
+    pub id: TaskId,
+}
+
+#[derive(Debug)]
+pub struct CommandLogTime {
+    pub id: TaskId,
+    pub duration: TimeDelta,
+    pub on: Option<NaiveDate>,
+}
+
+#[derive(Debug)]
+pub struct CommandTimesheet {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub grouping: TimesheetGrouping,
+    pub csv: bool,
+}
+
+#[derive(Debug)]
+pub struct CommandEstimate {
+    pub id: TaskId,
+    pub estimate: Estimate,
+}
+
+#[derive(Debug)]
+pub struct CommandMilestoneAdd {
+    pub name: String,
+    pub target: Option<NaiveDate>,
+}
+
+#[derive(Debug)]
+pub struct CommandMilestoneAssign {
+    pub id: TaskId,
+    pub name: String,
+}
+
+#[derive(Debug)]
+pub struct CommandSprintAdd {
+    pub name: String,
+    pub start: NaiveDate,
+    pub end: Option<NaiveDate>,
+    pub capacity: Option<Estimate>,
+}
+
+#[derive(Debug)]
+pub struct CommandSprintPlan {
+    pub name: String,
+    pub ids: Vec<TaskId>,
+}
+
+#[derive(Debug)]
+pub struct CommandSprintClose {
+    pub name: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct CommandChartBurndown {
+    pub milestone: String,
+    pub export: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct CommandChartThroughput {
+    pub weeks: u32,
+    pub export: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct CommandForecast {
+    pub milestone: Option<String>,
+    pub sprint: Option<String>,
+    pub filter: Option<Filter>,
+    pub weeks: u32,
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct CommandStats {
+    pub weeks: u32,
+    pub json: bool,
+}
+
+#[derive(Debug)]
+pub struct CommandHeatmap {
+    pub metric: HeatmapMetric,
+}
+
+#[derive(Debug)]
+pub struct CommandStandup {
+    pub since: Option<NaiveDate>,
+    pub format: StandupFormat,
+}
+
+#[derive(Debug)]
+pub struct CommandLog {
+    pub since: NaiveDate,
+    pub changes: bool,
+}
+
+#[derive(Debug)]
+pub struct CommandSearch {
+    pub query: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct CommandRecurStop {
+    pub id: TaskId,
 }
 
 #[derive(Debug)]
@@ -39,9 +223,38 @@ pub enum Command {
     Add(CommandAdd),
     Update(CommandUpdate),
     Delete(CommandDelete),
-    MarkInProgress(CommandMarkInProgress),
-    MarkDone(CommandMarkDone),
+    SetStatus(CommandSetStatus),
     List(CommandList),
+    Depend(CommandDepend),
+    Next,
+    RecurList,
+    RecurStop(CommandRecurStop),
+    Show(CommandShow),
+    Edit(CommandEdit),
+    Annotate(CommandAnnotate),
+    CheckAdd(CommandCheckAdd),
+    CheckDone(CommandCheckDone),
+    Start(CommandStart),
+    Stop,
+    LogTime(CommandLogTime),
+    Timesheet(CommandTimesheet),
+    Estimate(CommandEstimate),
+    ReportEstimates,
+    MilestoneAdd(CommandMilestoneAdd),
+    MilestoneAssign(CommandMilestoneAssign),
+    Roadmap,
+    SprintAdd(CommandSprintAdd),
+    SprintPlan(CommandSprintPlan),
+    SprintCurrent,
+    SprintClose(CommandSprintClose),
+    ChartBurndown(CommandChartBurndown),
+    ChartThroughput(CommandChartThroughput),
+    Forecast(CommandForecast),
+    Stats(CommandStats),
+    Heatmap(CommandHeatmap),
+    Standup(CommandStandup),
+    Log(CommandLog),
+    Search(CommandSearch),
 }
 
 pub fn parse_command(args: Vec<String>) -> Result<Command> {
@@ -53,57 +266,603 @@ pub fn parse_command(args: Vec<String>) -> Result<Command> {
         "add" => parse_add_command(args).map(Command::Add),
         "update" => parse_update_command(args).map(Command::Update),
         "delete" => parse_delete_command(args).map(Command::Delete),
-        "mark-in-progress" => parse_mark_in_progress_command(args).map(Command::MarkInProgress),
-        "mark-done" => parse_mark_done_command(args).map(Command::MarkDone),
+        "set-status" => parse_set_status_command(args).map(Command::SetStatus),
+        "mark-in-progress" => {
+            parse_mark_command(args, TaskStatus::InProgress).map(Command::SetStatus)
+        }
+        "mark-done" => parse_mark_command(args, TaskStatus::Done).map(Command::SetStatus),
         "list" => parse_list_command(args).map(Command::List),
+        "depend" => parse_depend_command(args).map(Command::Depend),
+        "next" => validate_args_length(&args, 2).map(|_| Command::Next),
+        "recur" => parse_recur_command(args),
+        "show" => parse_show_command(args).map(Command::Show),
+        "edit" => parse_edit_command(args).map(Command::Edit),
+        "annotate" => parse_annotate_command(args).map(Command::Annotate),
+        "check" => parse_check_command(args),
+        "start" => parse_start_command(args).map(Command::Start),
+        "stop" => validate_args_length(&args, 2).map(|_| Command::Stop),
+        "log-time" => parse_log_time_command(args).map(Command::LogTime),
+        "timesheet" => parse_timesheet_command(args).map(Command::Timesheet),
+        "estimate" => parse_estimate_command(args).map(Command::Estimate),
+        "report" => parse_report_command(args),
+        "milestone" => parse_milestone_command(args),
+        "roadmap" => validate_args_length(&args, 2).map(|_| Command::Roadmap),
+        "sprint" => parse_sprint_command(args),
+        "chart" => parse_chart_command(args),
+        "forecast" => parse_forecast_command(args).map(Command::Forecast),
+        "stats" => parse_stats_command(args).map(Command::Stats),
+        "heatmap" => parse_heatmap_command(args).map(Command::Heatmap),
+        "standup" => parse_standup_command(args).map(Command::Standup),
+        "log" => parse_log_command(args).map(Command::Log),
+        "search" => parse_search_command(args).map(Command::Search),
         _ => Err(Error::UnknownCommand { command: cmd }),
     }
 }
 
-fn parse_add_command(args: Vec<String>) -> Result<CommandAdd> {
+fn parse_add_command(mut args: Vec<String>) -> Result<CommandAdd> {
+    let today = Local::now().date_naive();
+    let due = take_option(&mut args, "--due")?
+        .map(|due| parse_date(&due, today))
+        .transpose()?;
+    let recur = take_option(&mut args, "--recur")?
+        .map(|recur| RecurrenceRule::parse(&recur, due.unwrap_or(today)))
+        .transpose()?;
+    let milestone = take_option(&mut args, "--milestone")?;
+    let body = take_option(&mut args, "--body")?.and_then(|body| clean_body(&body));
+    reject_unknown_options(&args)?;
     validate_args_length(&args, 3)?;
     let description = TaskDescription::new(args[2].clone())?;
-    Ok(CommandAdd { description })
+    Ok(CommandAdd {
+        description,
+        due,
+        recur,
+        milestone,
+        body,
+    })
 }
 
-fn parse_update_command(args: Vec<String>) -> Result<CommandUpdate> {
-    validate_args_length(&args, 4)?;
+fn parse_update_command(mut args: Vec<String>) -> Result<CommandUpdate> {
+    let selector = take_selector(&mut args, 4)?;
+    let description = TaskDescription::new(args[2].clone())?;
+    Ok(CommandUpdate {
+        selector,
+        description,
+    })
+}
+
+fn parse_delete_command(mut args: Vec<String>) -> Result<CommandDelete> {
+    let selector = take_selector(&mut args, 3)?;
+    Ok(CommandDelete { selector })
+}
+
+fn parse_set_status_command(mut args: Vec<String>) -> Result<CommandSetStatus> {
+    let selector = take_selector(&mut args, 4)?;
+    let status = TaskStatus::from_str(&args[2])?;
+    Ok(CommandSetStatus { selector, status })
+}
+
+fn parse_mark_command(mut args: Vec<String>, status: TaskStatus) -> Result<CommandSetStatus> {
+    let selector = take_selector(&mut args, 3)?;
+    Ok(CommandSetStatus { selector, status })
+}
+
+/// Takes `--where <filter>`, or else the task ids right after the command name.
+/// `expected` is the number of arguments when an id is given
+fn take_selector(args: &mut Vec<String>, expected: u8) -> Result<TaskSelector> {
+    match take_option(args, "--where")? {
+        Some(query) => {
+            validate_args_length(args, expected - 1)?;
+            let filter = Filter::parse(&query, Local::now().date_naive())?;
+            Ok(TaskSelector::Filter(filter))
+        }
+        None => {
+            validate_args_length(args, expected)?;
+            let ids = args.remove(2);
+            match ids.contains([',', '-']) {
+                true => parse_id_list(&ids).map(TaskSelector::Ids),
+                false => TaskId::new_from_string(ids).map(TaskSelector::Id),
+            }
+        }
+    }
+}
+
+/// Most ids a list can select, so that a range such as `1-4294967295` is
+/// rejected before being expanded
+const MAX_IDS_IN_LIST: usize = 10_000;
+
+/// Ids such as `1,4,7-12`, without duplicates
+fn parse_id_list(list: &str) -> Result<Vec<TaskId>> {
+    let mut ids: Vec<TaskId> = vec![];
+    let mut seen: HashSet<TaskId> = HashSet::new();
+    for part in list.split(',').map(str::trim) {
+        let range = match part.split_once('-') {
+            Some((first, last)) => {
+                let first = TaskId::new_from_string(first.trim().to_string())?;
+                let last = TaskId::new_from_string(last.trim().to_string())?;
+                if first > last {
+                    return Err(Error::InvalidIdRange {
+                        range: part.to_string(),
+                    });
+                }
+                if (last.value() - first.value()) as usize >= MAX_IDS_IN_LIST {
+                    return Err(Error::IdListTooLong {
+                        list: list.to_string(),
+                        max: MAX_IDS_IN_LIST,
+                    });
+                }
+                first.value()..=last.value()
+            }
+            None => {
+                let id = TaskId::new_from_string(part.to_string())?;
+                id.value()..=id.value()
+            }
+        };
+        for id in range {
+            let id = TaskId::new(id)?;
+            if seen.insert(id) {
+                ids.push(id);
+            }
+        }
+        if ids.len() > MAX_IDS_IN_LIST {
+            return Err(Error::IdListTooLong {
+                list: list.to_string(),
+                max: MAX_IDS_IN_LIST,
+            });
+        }
+    }
+    Ok(ids)
+}
+
+fn parse_list_command(mut args: Vec<String>) -> Result<CommandList> {
+    let dependency = if take_flag(&mut args, "--blocked") {
+        Some(DependencyFilter::Blocked)
+    } else if take_flag(&mut args, "--ready") {
+        Some(DependencyFilter::Ready)
+    } else {
+        None
+    };
+    let sort = take_option(&mut args, "--sort")?
+        .map(|sort| SortOrder::from_str(&sort))
+        .transpose()?;
+    let reverse = take_flag(&mut args, "--reverse");
+    let offset = take_parsed_option(&mut args, "--offset")?.unwrap_or(0);
+    let limit = take_parsed_option(&mut args, "--limit")?;
+    reject_unknown_options(&args)?;
+    let (status, filter) = match args.len() {
+        2 => (None, None),
+        3 if TaskStatus::from_str(&args[2]).is_ok() => {
+            (Some(TaskStatus::from_str(&args[2])?), None)
+        }
+        _ => {
+            let query = args[2..].join(" ");
+            (
+                None,
+                Some(Filter::parse(&query, Local::now().date_naive())?),
+            )
+        }
+    };
+    Ok(CommandList {
+        status,
+        dependency,
+        filter,
+        sort,
+        reverse,
+        offset,
+        limit,
+    })
+}
+
+fn parse_depend_command(mut args: Vec<String>) -> Result<CommandDepend> {
+    let on = take_option(&mut args, "--on")?.ok_or(Error::MissingOption {
+        option: "--on".to_string(),
+    })?;
+    reject_unknown_options(&args)?;
+    validate_args_length(&args, 3)?;
     let id = TaskId::new_from_string(args[2].clone())?;
-    let description = TaskDescription::new(args[3].clone())?;
-    Ok(CommandUpdate { id, description })
+    let on = TaskId::new_from_string(on)?;
+    Ok(CommandDepend { id, on })
 }
 
-fn parse_delete_command(args: Vec<String>) -> Result<CommandDelete> {
+fn parse_show_command(mut args: Vec<String>) -> Result<CommandShow> {
+    let json = take_flag(&mut args, "--json");
+    reject_unknown_options(&args)?;
     validate_args_length(&args, 3)?;
     let id = TaskId::new_from_string(args[2].clone())?;
-    Ok(CommandDelete { id })
+    Ok(CommandShow { id, json })
 }
 
-fn parse_mark_in_progress_command(args: Vec<String>) -> Result<CommandMarkInProgress> {
+fn parse_edit_command(args: Vec<String>) -> Result<CommandEdit> {
     validate_args_length(&args, 3)?;
     let id = TaskId::new_from_string(args[2].clone())?;
-    Ok(CommandMarkInProgress { id })
+    Ok(CommandEdit { id })
+}
+
+fn parse_annotate_command(args: Vec<String>) -> Result<CommandAnnotate> {
+    validate_args_length(&args, 4)?;
+    let id = TaskId::new_from_string(args[2].clone())?;
+    Ok(CommandAnnotate {
+        id,
+        text: args[3].clone(),
+    })
 }
 
-fn parse_mark_done_command(args: Vec<String>) -> Result<CommandMarkDone> {
+fn parse_check_command(args: Vec<String>) -> Result<Command> {
+    let subcommand = args.get(2).cloned().unwrap_or_default();
+    match subcommand.as_str() {
+        "add" => {
+            validate_args_length(&args, 5)?;
+            let id = TaskId::new_from_string(args[3].clone())?;
+            Ok(Command::CheckAdd(CommandCheckAdd {
+                id,
+                text: args[4].clone(),
+            }))
+        }
+        "done" => {
+            validate_args_length(&args, 5)?;
+            let id = TaskId::new_from_string(args[3].clone())?;
+            let number =
+                args[4]
+                    .parse::<usize>()
+                    .map_err(|_| Error::ChecklistItemMustBeNumber {
+                        item: args[4].clone(),
+                    })?;
+            Ok(Command::CheckDone(CommandCheckDone { id, number }))
+        }
+        _ => Err(Error::UnknownCommand {
+            command: format!("check {}", subcommand),
+        }),
+    }
+}
+
+fn parse_start_command(args: Vec<String>) -> Result<CommandStart> {
     validate_args_length(&args, 3)?;
     let id = TaskId::new_from_string(args[2].clone())?;
-    Ok(CommandMarkDone { id })
+    Ok(CommandStart { id })
 }
 
-fn parse_list_command(args: Vec<String>) -> Result<CommandList> {
-    if args.len() == 2 {
-        Ok(CommandList { status: None })
-    } else if args.len() == 3 {
-        let status = TaskStatus::from_str(&args[2])?;
-        Ok(CommandList {
-            status: Some(status),
-        })
-    } else {
-        Err(Error::TooManyArguments {
-            max: 3,
-            got: args.len() as u8,
+fn parse_log_time_command(mut args: Vec<String>) -> Result<CommandLogTime> {
+    let today = Local::now().date_naive();
+    let on = take_option(&mut args, "--on")?
+        .map(|on| parse_date(&on, today))
+        .transpose()?;
+    reject_unknown_options(&args)?;
+    validate_args_length(&args, 4)?;
+    let id = TaskId::new_from_string(args[2].clone())?;
+    let duration = parse_duration(&args[3])?;
+    Ok(CommandLogTime { id, duration, on })
+}
+
+fn parse_timesheet_command(mut args: Vec<String>) -> Result<CommandTimesheet> {
+    let today = Local::now().date_naive();
+    let week = take_flag(&mut args, "--week");
+    let csv = take_flag(&mut args, "--csv");
+    let from = take_option(&mut args, "--from")?
+        .map(|from| parse_date(&from, today))
+        .transpose()?;
+    let to = take_option(&mut args, "--to")?
+        .map(|to| parse_date(&to, today))
+        .transpose()?;
+    let grouping = match take_option(&mut args, "--by")?.as_deref() {
+        None | Some("day") => TimesheetGrouping::Day,
+        Some("task") => TimesheetGrouping::Task,
+        Some(other) => {
+            return Err(Error::UnknownOption {
+                option: format!("--by {}", other),
+            })
+        }
+    };
+    reject_unknown_options(&args)?;
+    validate_args_length(&args, 2)?;
+    if week && (from.is_some() || to.is_some()) {
+        return Err(Error::ConflictingOptions {
+            option: "--week".to_string(),
+            other: match from {
+                Some(_) => "--from".to_string(),
+                None => "--to".to_string(),
+            },
+        });
+    }
+    let (from, to) = match (from, to) {
+        (None, None) => week_bounds(today),
+        (Some(from), to) => (from, to.unwrap_or(today)),
+        (None, Some(_)) => {
+            return Err(Error::MissingOption {
+                option: "--from".to_string(),
+            })
+        }
+    };
+    Ok(CommandTimesheet {
+        from,
+        to,
+        grouping,
+        csv,
+    })
+}
+
+fn parse_estimate_command(args: Vec<String>) -> Result<CommandEstimate> {
+    validate_args_length(&args, 4)?;
+    let id = TaskId::new_from_string(args[2].clone())?;
+    let estimate = Estimate::from_str(&args[3])?;
+    Ok(CommandEstimate { id, estimate })
+}
+
+fn parse_report_command(args: Vec<String>) -> Result<Command> {
+    let report = args.get(2).cloned().unwrap_or_default();
+    match report.as_str() {
+        "estimates" => validate_args_length(&args, 3).map(|_| Command::ReportEstimates),
+        _ => Err(Error::UnknownCommand {
+            command: format!("report {}", report),
+        }),
+    }
+}
+
+fn parse_milestone_command(mut args: Vec<String>) -> Result<Command> {
+    let subcommand = args.get(2).cloned().unwrap_or_default();
+    match subcommand.as_str() {
+        "add" => {
+            let today = Local::now().date_naive();
+            let target = take_option(&mut args, "--target")?
+                .map(|target| parse_date(&target, today))
+                .transpose()?;
+            reject_unknown_options(&args)?;
+            validate_args_length(&args, 4)?;
+            Ok(Command::MilestoneAdd(CommandMilestoneAdd {
+                name: args[3].clone(),
+                target,
+            }))
+        }
+        "assign" => {
+            validate_args_length(&args, 5)?;
+            let id = TaskId::new_from_string(args[3].clone())?;
+            Ok(Command::MilestoneAssign(CommandMilestoneAssign {
+                id,
+                name: args[4].clone(),
+            }))
+        }
+        _ => Err(Error::UnknownCommand {
+            command: format!("milestone {}", subcommand),
+        }),
+    }
+}
+
+fn parse_sprint_command(mut args: Vec<String>) -> Result<Command> {
+    let subcommand = args.get(2).cloned().unwrap_or_default();
+    match subcommand.as_str() {
+        "add" => {
+            let today = Local::now().date_naive();
+            let start = take_option(&mut args, "--start")?
+                .ok_or(Error::MissingOption {
+                    option: "--start".to_string(),
+                })
+                .and_then(|start| parse_date(&start, today))?;
+            let end = take_option(&mut args, "--end")?
+                .map(|end| parse_date(&end, today))
+                .transpose()?;
+            let capacity = take_option(&mut args, "--capacity")?
+                .map(|capacity| Estimate::from_str(&capacity))
+                .transpose()?;
+            reject_unknown_options(&args)?;
+            validate_args_length(&args, 4)?;
+            Ok(Command::SprintAdd(CommandSprintAdd {
+                name: args[3].clone(),
+                start,
+                end,
+                capacity,
+            }))
+        }
+        "plan" => {
+            if args.len() < 5 {
+                return Err(Error::WrongNumberOfArguments {
+                    expected: 5,
+                    got: args.len() as u8,
+                });
+            }
+            let ids = args[4..]
+                .iter()
+                .map(|id| TaskId::new_from_string(id.clone()))
+                .collect::<Result<Vec<TaskId>>>()?;
+            Ok(Command::SprintPlan(CommandSprintPlan {
+                name: args[3].clone(),
+                ids,
+            }))
+        }
+        "current" => validate_args_length(&args, 3).map(|_| Command::SprintCurrent),
+        "close" => match args.len() {
+            3 => Ok(Command::SprintClose(CommandSprintClose { name: None })),
+            4 => Ok(Command::SprintClose(CommandSprintClose {
+                name: Some(args[3].clone()),
+            })),
+            _ => Err(Error::TooManyArguments {
+                max: 4,
+                got: args.len() as u8,
+            }),
+        },
+        _ => Err(Error::UnknownCommand {
+            command: format!("sprint {}", subcommand),
+        }),
+    }
+}
+
+fn parse_chart_command(mut args: Vec<String>) -> Result<Command> {
+    let subcommand = args.get(2).cloned().unwrap_or_default();
+    let export = take_option(&mut args, "--export")?.map(PathBuf::from);
+    match subcommand.as_str() {
+        "burndown" => {
+            let milestone = take_option(&mut args, "--milestone")?.ok_or(Error::MissingOption {
+                option: "--milestone".to_string(),
+            })?;
+            reject_unknown_options(&args)?;
+            validate_args_length(&args, 3)?;
+            Ok(Command::ChartBurndown(CommandChartBurndown {
+                milestone,
+                export,
+            }))
+        }
+        "throughput" => {
+            let weeks = take_weeks(&mut args)?;
+            reject_unknown_options(&args)?;
+            validate_args_length(&args, 3)?;
+            Ok(Command::ChartThroughput(CommandChartThroughput {
+                weeks,
+                export,
+            }))
+        }
+        _ => Err(Error::UnknownCommand {
+            command: format!("chart {}", subcommand),
+        }),
+    }
+}
+
+fn parse_forecast_command(mut args: Vec<String>) -> Result<CommandForecast> {
+    let milestone = take_option(&mut args, "--milestone")?;
+    let sprint = take_option(&mut args, "--sprint")?;
+    let filter = take_option(&mut args, "--where")?
+        .map(|query| Filter::parse(&query, Local::now().date_naive()))
+        .transpose()?;
+    let weeks = take_weeks(&mut args)?;
+    let seed = take_parsed_option(&mut args, "--seed")?;
+    reject_unknown_options(&args)?;
+    validate_args_length(&args, 2)?;
+    Ok(CommandForecast {
+        milestone,
+        sprint,
+        filter,
+        weeks,
+        seed,
+    })
+}
+
+fn parse_stats_command(mut args: Vec<String>) -> Result<CommandStats> {
+    let json = take_flag(&mut args, "--json");
+    let weeks = take_weeks(&mut args)?;
+    reject_unknown_options(&args)?;
+    validate_args_length(&args, 2)?;
+    Ok(CommandStats { weeks, json })
+}
+
+fn parse_heatmap_command(mut args: Vec<String>) -> Result<CommandHeatmap> {
+    let metric = match take_flag(&mut args, "--hours") {
+        true => HeatmapMetric::Hours,
+        false => HeatmapMetric::Completed,
+    };
+    reject_unknown_options(&args)?;
+    validate_args_length(&args, 2)?;
+    Ok(CommandHeatmap { metric })
+}
+
+fn parse_standup_command(mut args: Vec<String>) -> Result<CommandStandup> {
+    let today = Local::now().date_naive();
+    let since = take_option(&mut args, "--since")?
+        .map(|since| parse_past_date(&since, today))
+        .transpose()?;
+    let format = match take_option(&mut args, "--format")?.as_deref() {
+        None | Some("markdown") => StandupFormat::Markdown,
+        Some("slack") => StandupFormat::Slack,
+        Some("plain") => StandupFormat::Plain,
+        Some(other) => {
+            return Err(Error::InvalidOptionValue {
+                option: "--format".to_string(),
+                value: other.to_string(),
+            })
+        }
+    };
+    reject_unknown_options(&args)?;
+    validate_args_length(&args, 2)?;
+    Ok(CommandStandup { since, format })
+}
+
+fn parse_log_command(mut args: Vec<String>) -> Result<CommandLog> {
+    let today = Local::now().date_naive();
+    let changes = take_flag(&mut args, "--changes");
+    let since = match take_option(&mut args, "--since")? {
+        Some(since) => parse_past_date(&since, today)?,
+        None => week_bounds(today).0,
+    };
+    reject_unknown_options(&args)?;
+    validate_args_length(&args, 2)?;
+    Ok(CommandLog { since, changes })
+}
+
+fn parse_search_command(mut args: Vec<String>) -> Result<CommandSearch> {
+    let limit = take_parsed_option(&mut args, "--limit")?;
+    reject_unknown_options(&args)?;
+    let query = args[2..].join(" ").trim().to_string();
+    if query.is_empty() {
+        return Err(Error::EmptySearchQuery);
+    }
+    Ok(CommandSearch { query, limit })
+}
+
+fn parse_recur_command(args: Vec<String>) -> Result<Command> {
+    let subcommand = args.get(2).cloned().unwrap_or_default();
+    match subcommand.as_str() {
+        "list" => validate_args_length(&args, 3).map(|_| Command::RecurList),
+        "stop" => {
+            validate_args_length(&args, 4)?;
+            let id = TaskId::new_from_string(args[3].clone())?;
+            Ok(Command::RecurStop(CommandRecurStop { id }))
+        }
+        _ => Err(Error::UnknownCommand {
+            command: format!("recur {}", subcommand),
+        }),
+    }
+}
+
+/// Removes `name` and the value following it from `args`
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>> {
+    let Some(position) = args.iter().position(|arg| arg == name) else {
+        return Ok(None);
+    };
+    if position + 1 >= args.len() {
+        return Err(Error::MissingOptionValue {
+            option: name.to_string(),
+        });
+    }
+    let value = args.remove(position + 1);
+    args.remove(position);
+    Ok(Some(value))
+}
+
+fn take_parsed_option<T: FromStr>(args: &mut Vec<String>, name: &str) -> Result<Option<T>> {
+    take_option(args, name)?
+        .map(|value| {
+            value.parse::<T>().map_err(|_| Error::InvalidOptionValue {
+                option: name.to_string(),
+                value,
+            })
         })
+        .transpose()
+}
+
+/// Number of past weeks to look at, from one to `MAX_HISTORY_WEEKS`
+fn take_weeks(args: &mut Vec<String>) -> Result<u32> {
+    match take_parsed_option::<u32>(args, "--weeks")? {
+        Some(weeks) if weeks == 0 || weeks > MAX_HISTORY_WEEKS => Err(Error::InvalidOptionValue {
+            option: "--weeks".to_string(),
+            value: weeks.to_string(),
+        }),
+        Some(weeks) => Ok(weeks),
+        None => Ok(DEFAULT_HISTORY_WEEKS),
+    }
+}
+
+/// Removes `name` from `args`, returning whether it was present
+fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    match args.iter().position(|arg| arg == name) {
+        Some(position) => {
+            args.remove(position);
+            true
+        }
+        None => false,
+    }
+}
+
+fn reject_unknown_options(args: &[String]) -> Result<()> {
+    match args.iter().find(|arg| arg.starts_with("--")) {
+        Some(option) => Err(Error::UnknownOption {
+            option: option.clone(),
+        }),
+        None => Ok(()),
     }
 }
 
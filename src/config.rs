@@ -0,0 +1,116 @@
+use std::{collections::HashMap, path::PathBuf, sync::OnceLock};
+
+use serde::Deserialize;
+
+use crate::{sort::SortOrder, task::TaskStatus, timesheet::RoundingConfig, Error, Result};
+
+const CONFIG_FILENAME: &str = ".roadmap-task-tracker.config.json";
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+fn get_config_path() -> &'static PathBuf {
+    CONFIG_PATH.get_or_init(|| {
+        let mut path = home::home_dir().unwrap_or_else(|| panic!("{}", Error::HomePathNotFound));
+        path.push(CONFIG_FILENAME);
+        path
+    })
+}
+
+/// What to do when a task is moved to in progress while its dependencies are not done yet
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockedTaskPolicy {
+    #[default]
+    Warn,
+    Refuse,
+}
+
+/// Enabled statuses and, for each of them, the statuses a task can move to.
+/// Statuses are picked among the built-in `TaskStatus` ones, not defined here.
+/// New tasks always start as todo.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct WorkflowConfig {
+    pub statuses: Vec<TaskStatus>,
+    pub transitions: HashMap<TaskStatus, Vec<TaskStatus>>,
+}
+
+impl Default for WorkflowConfig {
+    fn default() -> Self {
+        Self {
+            statuses: vec![TaskStatus::Todo, TaskStatus::InProgress, TaskStatus::Done],
+            transitions: HashMap::from([
+                (
+                    TaskStatus::Todo,
+                    vec![TaskStatus::InProgress, TaskStatus::Done],
+                ),
+                (
+                    TaskStatus::InProgress,
+                    vec![TaskStatus::Todo, TaskStatus::Done],
+                ),
+                (
+                    TaskStatus::Done,
+                    vec![TaskStatus::Todo, TaskStatus::InProgress],
+                ),
+            ]),
+        }
+    }
+}
+
+impl WorkflowConfig {
+    pub fn allowed_transitions(&self, from: TaskStatus) -> Vec<TaskStatus> {
+        self.transitions
+            .get(&from)
+            .map(|to| {
+                to.iter()
+                    .filter(|status| self.statuses.contains(status))
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn check_transition(&self, from: TaskStatus, to: TaskStatus) -> Result<()> {
+        let allowed = self.allowed_transitions(from);
+        if allowed.contains(&to) {
+            return Ok(());
+        }
+        let allowed: Vec<String> = allowed.iter().map(|status| status.to_string()).collect();
+        Err(Error::IllegalTransition {
+            from: from.to_string(),
+            to: to.to_string(),
+            allowed: allowed.join(", "),
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub blocked_task_policy: BlockedTaskPolicy,
+    pub workflow: WorkflowConfig,
+    pub timesheet_rounding: RoundingConfig,
+    /// Used by `list` when no `--sort` is given
+    pub default_sort: SortOrder,
+    /// Marks a task done once every item of its checklist is checked
+    pub auto_complete_checklists: bool,
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        match get_config_path().exists() {
+            true => {
+                let content = std::fs::read_to_string(get_config_path())
+                    .map_err(|_| Error::FailedToLoadConfig)?;
+                let config: Config =
+                    serde_json::from_str(&content).map_err(|_| Error::FailedToLoadConfig)?;
+                if !config.workflow.statuses.contains(&TaskStatus::Todo) {
+                    return Err(Error::InvalidConfig {
+                        reason: "workflow statuses must include todo".to_string(),
+                    });
+                }
+                Ok(config)
+            }
+            false => Ok(Self::default()),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use chrono::{Datelike, Days, NaiveDate, TimeDelta, Weekday};
+
+use crate::{Error, Result};
+
+/// Parses `today`, `tomorrow`, `yesterday`, a weekday name (its next occurrence,
+/// today included) or an ISO date (`2026-12-01`)
+pub fn parse_date(input: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let invalid_date = || Error::InvalidDate {
+        date: input.to_string(),
+    };
+    match input.to_lowercase().as_str() {
+        "today" => Ok(today),
+        "tomorrow" => today
+            .checked_add_days(Days::new(1))
+            .ok_or_else(invalid_date),
+        "yesterday" => today
+            .checked_sub_days(Days::new(1))
+            .ok_or_else(invalid_date),
+        s => match s.parse::<Weekday>() {
+            Ok(weekday) => {
+                let offset = (7 + weekday.num_days_from_monday()
+                    - today.weekday().num_days_from_monday())
+                    % 7;
+                today
+                    .checked_add_days(Days::new(offset as u64))
+                    .ok_or_else(invalid_date)
+            }
+            Err(_) => NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| invalid_date()),
+        },
+    }
+}
+
+/// Same as `parse_date`, except weekday names stand for their last occurrence
+pub fn parse_past_date(input: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let date = parse_date(input, today)?;
+    match input.to_lowercase().parse::<Weekday>() {
+        Ok(_) if date > today => Ok(date - Days::new(7)),
+        _ => Ok(date),
+    }
+}
+
+/// Monday and Sunday of the week containing `day`
+pub fn week_bounds(day: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let monday = day - Days::new(day.weekday().num_days_from_monday() as u64);
+    (monday, monday + Days::new(6))
+}
+
+pub fn last_day_of_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = match month {
+        12 => (year + 1, 1),
+        _ => (year, month + 1),
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|date| date.pred_opt())
+        .map(|date| date.day())
+        .unwrap_or(28)
+}
+
+/// Longest duration accepted, a year
+const MAX_DURATION_MINUTES: i64 = 366 * 24 * 60;
+
+/// Parses durations such as `1h30m`, `2h` or `45m`
+pub fn parse_duration(input: &str) -> Result<TimeDelta> {
+    let invalid_duration = || Error::InvalidDuration {
+        duration: input.to_string(),
+    };
+    let mut minutes: i64 = 0;
+    let mut number = String::new();
+    for c in input.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' => {
+                let value = number.parse::<i64>().map_err(|_| invalid_duration())?;
+                minutes = match c {
+                    'h' => value.checked_mul(60),
+                    _ => Some(value),
+                }
+                .and_then(|value| minutes.checked_add(value))
+                .filter(|minutes| *minutes <= MAX_DURATION_MINUTES)
+                .ok_or_else(invalid_duration)?;
+                number.clear();
+            }
+            _ => return Err(invalid_duration()),
+        }
+    }
+    if !number.is_empty() || minutes == 0 {
+        return Err(invalid_duration());
+    }
+    Ok(TimeDelta::minutes(minutes))
+}
+
+/// Formats as `2d 3h 5m`, dropping leading zero units
+pub fn format_duration(duration: TimeDelta) -> String {
+    let minutes = duration.num_minutes().max(0);
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h {}m", days, hours, minutes),
+    }
+}
+
+/// `3 days ago`, `in 2 hours` or `just now`, in the largest whole unit.
+/// `delta` is the time from now to the moment described
+pub fn format_relative(delta: TimeDelta) -> String {
+    let seconds = delta.num_seconds();
+    let units = [
+        ("year", 365 * 24 * 3600),
+        ("month", 30 * 24 * 3600),
+        ("week", 7 * 24 * 3600),
+        ("day", 24 * 3600),
+        ("hour", 3600),
+        ("minute", 60),
+    ];
+    let Some((unit, count)) = units
+        .iter()
+        .map(|(unit, length)| (unit, seconds.abs() / length))
+        .find(|(_, count)| *count > 0)
+    else {
+        return "just now".to_string();
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    match seconds < 0 {
+        true => format!("{} {}{} ago", count, unit, plural),
+        false => format!("in {} {}{}", count, unit, plural),
+    }
+}
+
+/// `today`, `tomorrow`, `in 3 days`, `yesterday` or `2 days ago`
+pub fn format_relative_date(date: NaiveDate, today: NaiveDate) -> String {
+    match (date - today).num_days() {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        -1 => "yesterday".to_string(),
+        days if days > 0 => format!("in {} days", days),
+        days => format!("{} days ago", -days),
+    }
+}
@@ -1,6 +1,12 @@
-use std::{path::PathBuf, sync::OnceLock};
+use std::{collections::HashMap, path::PathBuf, sync::OnceLock};
+
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    milestone::Milestone,
+    query::TaskQuery,
+    search::{SearchHit, SearchIndex},
+    sprint::Sprint,
     task::{Task, TaskId},
     Error, Result,
 };
@@ -18,78 +24,258 @@ fn get_db_path() -> &'static PathBuf {
 
 pub trait TaskStorage {
     fn create_task(&mut self, task: Task) -> Result<()>;
-    fn count_tasks(&self) -> u32;
-    fn get_task(&mut self, id: &TaskId) -> Option<&Task>;
-    fn get_tasks(&self) -> &Vec<Task>;
+    fn get_task(&self, id: &TaskId) -> Result<Option<&Task>>;
+    fn get_tasks(&self) -> Result<&[Task]>;
+    /// Tasks matching `query`, sorted and paged as it asks
+    fn query(&self, query: &TaskQuery) -> Result<Vec<&Task>>;
     fn update_task(&mut self, id: &TaskId, task: Task) -> Result<()>;
     fn delete_task(&mut self, id: &TaskId) -> Result<()>;
+    /// Tasks matching `query`, best matches first
+    fn search(&self, query: &str) -> Result<Vec<SearchHit>>;
+    fn create_milestone(&mut self, milestone: Milestone) -> Result<()>;
+    fn get_milestones(&self) -> Result<&[Milestone]>;
+    fn create_sprint(&mut self, sprint: Sprint) -> Result<()>;
+    fn get_sprints(&self) -> Result<&[Sprint]>;
+    fn update_sprint(&mut self, name: &str, sprint: Sprint) -> Result<()>;
+    /// Stages every change until `commit` or `rollback`
+    fn begin(&mut self);
+    /// Persists the staged changes in a single write
+    fn commit(&mut self) -> Result<()>;
+    /// Discards the staged changes
+    fn rollback(&mut self);
+}
+
+#[derive(Serialize, Deserialize)]
+struct DbData {
+    tasks: Vec<Task>,
+    #[serde(default)]
+    milestones: Vec<Milestone>,
+    #[serde(default)]
+    sprints: Vec<Sprint>,
+}
+
+/// Files written before milestones existed only hold the list of tasks
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DbFile {
+    Current(DbData),
+    Legacy(Vec<Task>),
 }
 
 pub struct Db {
-    pub tasks: Vec<Task>,
+    path: PathBuf,
+    tasks: Vec<Task>,
+    milestones: Vec<Milestone>,
+    sprints: Vec<Sprint>,
+    /// Position of each task in `tasks`
+    id_index: HashMap<TaskId, usize>,
+    search_index: SearchIndex,
+    /// State before the current transaction, restored on rollback
+    snapshot: Option<DbData>,
+    /// Set when deletes moved tasks out of id order
+    unordered: bool,
 }
 
 impl Db {
     pub fn new() -> Result<Self> {
-        match get_db_path().exists() {
+        Self::open(get_db_path().clone())
+    }
+
+    /// The database stored at `path`, empty until the first write when the file doesn't exist
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let data = match path.exists() {
             true => {
-                let content = std::fs::read_to_string(DB_PATH.get().expect(""))
+                let content = std::fs::read_to_string(&path)
                     .map_err(|_| Error::FailedToAccessPersistedData)?;
-                let tasks: Vec<Task> = serde_json::from_str(&content)
+                let data: DbFile = serde_json::from_str(&content)
                     .map_err(|_| Error::FailedToAccessPersistedData)?;
-                Ok(Self { tasks })
+                match data {
+                    DbFile::Current(data) => data,
+                    DbFile::Legacy(tasks) => DbData {
+                        tasks,
+                        milestones: vec![],
+                        sprints: vec![],
+                    },
+                }
             }
-            false => Ok(Self { tasks: vec![] }),
+            false => DbData {
+                tasks: vec![],
+                milestones: vec![],
+                sprints: vec![],
+            },
+        };
+        Ok(Self::from_data(path, data))
+    }
+
+    fn from_data(path: PathBuf, data: DbData) -> Self {
+        Self {
+            path,
+            id_index: id_index(&data.tasks),
+            search_index: SearchIndex::new(&data.tasks),
+            tasks: data.tasks,
+            milestones: data.milestones,
+            sprints: data.sprints,
+            snapshot: None,
+            unordered: false,
+        }
+    }
+
+    /// Puts the tasks back in id order, their creation order, once the deletes
+    /// of a transaction are done
+    fn restore_order(&mut self) {
+        if self.unordered {
+            self.tasks.sort_by_key(|task| task.id);
+            self.id_index = id_index(&self.tasks);
+            self.unordered = false;
+        }
+    }
+
+    /// Writes to a temporary file first, so that the file is never left half
+    /// written. Does nothing during a transaction
+    fn persist(&self) -> Result<()> {
+        if self.snapshot.is_some() {
+            return Ok(());
         }
+        let content = serde_json::json!({
+            "tasks": &self.tasks,
+            "milestones": &self.milestones,
+            "sprints": &self.sprints,
+        })
+        .to_string();
+        let temp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&temp_path, content)
+            .and_then(|_| std::fs::rename(&temp_path, &self.path))
+            .map_err(|_| Error::FailedToPersistChanges)
+    }
+
+    fn position(&self, id: &TaskId) -> Result<usize> {
+        self.id_index
+            .get(id)
+            .copied()
+            .ok_or(Error::TaskNotFound { id: id.to_string() })
     }
 }
 
+fn id_index(tasks: &[Task]) -> HashMap<TaskId, usize> {
+    tasks
+        .iter()
+        .enumerate()
+        .map(|(position, task)| (task.id, position))
+        .collect()
+}
+
 impl TaskStorage for Db {
     fn create_task(&mut self, task: Task) -> Result<()> {
-        let tasks = &mut self.tasks;
-        tasks.push(task);
-        let content = serde_json::json!(tasks).to_string();
-        std::fs::write(get_db_path(), content).map_err(|_| Error::FailedToPersistChanges)?;
-        Ok(())
+        self.search_index.insert(&task);
+        self.id_index.insert(task.id, self.tasks.len());
+        self.tasks.push(task);
+        self.persist()
     }
 
-    fn count_tasks(&self) -> u32 {
-        self.tasks.len() as u32
+    fn get_task(&self, id: &TaskId) -> Result<Option<&Task>> {
+        Ok(self.id_index.get(id).map(|position| &self.tasks[*position]))
     }
 
-    fn get_task(&mut self, id: &TaskId) -> Option<&Task> {
-        self.tasks.iter().find(|task| task.id == *id)
+    fn get_tasks(&self) -> Result<&[Task]> {
+        Ok(&self.tasks)
     }
 
-    fn get_tasks(&self) -> &Vec<Task> {
-        &self.tasks
+    /// Only the tasks with the requested ids are looked at, when there are some
+    fn query(&self, query: &TaskQuery) -> Result<Vec<&Task>> {
+        let candidates = match &query.ids {
+            Some(ids) => ids
+                .iter()
+                .filter_map(|id| self.id_index.get(id))
+                .map(|position| &self.tasks[*position])
+                .collect(),
+            None => self.tasks.iter().collect(),
+        };
+        Ok(query.run(candidates, &self.tasks))
     }
 
     fn update_task(&mut self, id: &TaskId, task: Task) -> Result<()> {
-        let position = self
-            .tasks
-            .iter()
-            .position(|task| task.id == *id)
-            .ok_or(Error::TaskNotFound { id: id.to_string() })?;
+        let position = self.position(id)?;
+        self.search_index.insert(&task);
         self.tasks[position] = task;
-        let content = serde_json::json!(&self.tasks).to_string();
-        std::fs::write(get_db_path(), content).map_err(|_| Error::FailedToPersistChanges)?;
-        Ok(())
+        self.persist()
     }
 
+    /// Moves the last task into the deleted one's place, so that deleting
+    /// many tasks in a transaction doesn't shift the others each time
     fn delete_task(&mut self, id: &TaskId) -> Result<()> {
-        let task_count = self.tasks.len();
-        self.tasks = self
-            .tasks
+        let position = self.position(id)?;
+        self.tasks.swap_remove(position);
+        self.id_index.remove(id);
+        if let Some(moved) = self.tasks.get(position) {
+            self.id_index.insert(moved.id, position);
+            self.unordered = true;
+        }
+        self.search_index.remove(id);
+        if self.snapshot.is_none() {
+            self.restore_order();
+        }
+        self.persist()
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<SearchHit>> {
+        Ok(self.search_index.search(query))
+    }
+
+    fn create_milestone(&mut self, milestone: Milestone) -> Result<()> {
+        self.milestones.push(milestone);
+        self.persist()
+    }
+
+    fn get_milestones(&self) -> Result<&[Milestone]> {
+        Ok(&self.milestones)
+    }
+
+    fn create_sprint(&mut self, sprint: Sprint) -> Result<()> {
+        self.sprints.push(sprint);
+        self.persist()
+    }
+
+    fn get_sprints(&self) -> Result<&[Sprint]> {
+        Ok(&self.sprints)
+    }
+
+    fn update_sprint(&mut self, name: &str, sprint: Sprint) -> Result<()> {
+        let position = self
+            .sprints
             .iter()
-            .filter(|task| task.id != *id)
-            .cloned()
-            .collect();
-        if self.tasks.len() == task_count {
-            return Err(Error::TaskNotFound { id: id.to_string() });
+            .position(|sprint| sprint.name == name)
+            .ok_or(Error::SprintNotFound {
+                name: name.to_string(),
+            })?;
+        self.sprints[position] = sprint;
+        self.persist()
+    }
+
+    fn begin(&mut self) {
+        if self.snapshot.is_none() {
+            self.snapshot = Some(DbData {
+                tasks: self.tasks.clone(),
+                milestones: self.milestones.clone(),
+                sprints: self.sprints.clone(),
+            });
+        }
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        let Some(snapshot) = self.snapshot.take() else {
+            return Ok(());
+        };
+        self.restore_order();
+        let result = self.persist();
+        if result.is_err() {
+            *self = Self::from_data(self.path.clone(), snapshot);
+        }
+        result
+    }
+
+    fn rollback(&mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            *self = Self::from_data(self.path.clone(), snapshot);
         }
-        let content = serde_json::json!(&self.tasks).to_string();
-        std::fs::write(get_db_path(), content).map_err(|_| Error::FailedToPersistChanges)?;
-        Ok(())
     }
 }
@@ -0,0 +1,51 @@
+use crate::task::{Task, TaskId};
+
+/// Ids of the dependencies of `task` that are not closed yet.
+/// Dependencies pointing to deleted tasks are ignored.
+pub fn blocked_by(task: &Task, tasks: &[Task]) -> Vec<TaskId> {
+    task.depends_on
+        .iter()
+        .filter(|id| {
+            tasks
+                .iter()
+                .any(|other| other.id == **id && !other.status.is_closed())
+        })
+        .copied()
+        .collect()
+}
+
+pub fn is_blocked(task: &Task, tasks: &[Task]) -> bool {
+    !blocked_by(task, tasks).is_empty()
+}
+
+pub fn is_ready(task: &Task, tasks: &[Task]) -> bool {
+    !task.status.is_closed() && !is_blocked(task, tasks)
+}
+
+/// Whether making `id` depend on `on` would close a loop, i.e. `on` already
+/// depends (directly or transitively) on `id`
+pub fn creates_cycle(tasks: &[Task], id: &TaskId, on: &TaskId) -> bool {
+    let mut pending = vec![*on];
+    let mut visited: Vec<TaskId> = vec![];
+    while let Some(current) = pending.pop() {
+        if current == *id {
+            return true;
+        }
+        if visited.contains(&current) {
+            continue;
+        }
+        visited.push(current);
+        if let Some(task) = tasks.iter().find(|task| task.id == current) {
+            pending.extend(task.depends_on.iter().copied());
+        }
+    }
+    false
+}
+
+/// Number of open tasks waiting on `task` to be done
+pub fn count_dependents(task: &Task, tasks: &[Task]) -> usize {
+    tasks
+        .iter()
+        .filter(|other| !other.status.is_closed() && other.depends_on.contains(&task.id))
+        .count()
+}
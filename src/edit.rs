@@ -0,0 +1,244 @@
+use std::{
+    collections::hash_map::RandomState,
+    fs::{File, OpenOptions},
+    hash::{BuildHasher, Hasher},
+    io::{ErrorKind, Write},
+    path::PathBuf,
+    process::Command,
+};
+
+use chrono::NaiveDate;
+
+use crate::{
+    datetime::parse_date,
+    recurrence::RecurrenceRule,
+    task::{clean_body, Estimate, Task, TaskDescription, TaskId, TaskStatus},
+    Error, Result,
+};
+
+/// The fields of a task that can be changed in the editor
+#[derive(Debug, PartialEq)]
+pub struct TaskEdit {
+    pub description: TaskDescription,
+    pub status: TaskStatus,
+    pub due: Option<NaiveDate>,
+    pub recur: Option<RecurrenceRule>,
+    pub milestone: Option<String>,
+    pub estimate: Option<Estimate>,
+    pub depends_on: Vec<TaskId>,
+    pub body: Option<String>,
+}
+
+impl TaskEdit {
+    pub fn from_task(task: &Task) -> Self {
+        TaskEdit {
+            description: task.description.clone(),
+            status: task.status,
+            due: task.due,
+            recur: task.recur,
+            milestone: task.milestone.clone(),
+            estimate: task.estimate,
+            depends_on: task.depends_on.clone(),
+            body: task.body.clone(),
+        }
+    }
+
+    /// Parses `field: value` lines, ignoring blank lines and `#` comments, then
+    /// the body below the separator as is. Fields left out or empty are cleared.
+    /// `None` when there is nothing but comments, i.e. the edit was cancelled
+    pub fn parse(text: &str, today: NaiveDate) -> Result<Option<Self>> {
+        let (text, body) = match text.split_once(BODY_SEPARATOR) {
+            Some((text, body)) => (text, clean_body(body)),
+            None => (text, None),
+        };
+        let mut fields: Vec<(&str, &str)> = vec![];
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid_line = |message: &str| Error::InvalidEdit {
+                line: line.to_string(),
+                message: message.to_string(),
+            };
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| invalid_line("expected `field: value`"))?;
+            let name = name.trim();
+            if !EDIT_FIELDS.contains(&name) {
+                return Err(invalid_line(&format!("unknown field `{}`", name)));
+            }
+            if fields.iter().any(|(other, _)| *other == name) {
+                return Err(invalid_line(&format!("`{}` is set twice", name)));
+            }
+            fields.push((name, value.trim()));
+        }
+        if fields.is_empty() && body.is_none() {
+            return Ok(None);
+        }
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|(other, _)| *other == name)
+                .map(|(_, value)| *value)
+                .filter(|value| !value.is_empty())
+        };
+
+        let description = TaskDescription::new(field("description").unwrap_or("").to_string())?;
+        let status = TaskStatus::from_str(field("status").unwrap_or(""))?;
+        let due = field("due").map(|due| parse_date(due, today)).transpose()?;
+        let recur = field("recur")
+            .map(|recur| RecurrenceRule::parse(recur, due.unwrap_or(today)))
+            .transpose()?;
+        let estimate = field("estimate").map(Estimate::from_str).transpose()?;
+        let depends_on = field("depends_on")
+            .map(|ids| {
+                ids.split(',')
+                    .map(|id| TaskId::new_from_string(id.trim().to_string()))
+                    .collect::<Result<Vec<TaskId>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+        Ok(Some(TaskEdit {
+            description,
+            status,
+            due,
+            recur,
+            milestone: field("milestone").map(str::to_string),
+            estimate,
+            depends_on,
+            body,
+        }))
+    }
+}
+
+const EDIT_FIELDS: [&str; 7] = [
+    "description",
+    "status",
+    "due",
+    "recur",
+    "milestone",
+    "estimate",
+    "depends_on",
+];
+
+/// The text opened in the editor, with the other fields as comments
+pub fn render_task_edit(task: &Task) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    let depends_on: Vec<String> = task.depends_on.iter().map(|id| id.to_string()).collect();
+    format!(
+        r#"# Editing task {id}. Lines starting with # are ignored above the body, and empty
+# fields are cleared.
+# Statuses: todo, in_progress, in_review, blocked, done, cancelled
+# Recurrence: daily, weekly, weekdays, monthly, monthly:<day>, every:<days>
+# Estimates: 1h30m, 45m or 5pt. Dependencies: ids separated by commas
+# Delete every line to cancel.
+# sprint: {sprint} (change it with `rtask sprint plan`)
+description: {description}
+status: {status}
+due: {due}
+recur: {recur}
+milestone: {milestone}
+estimate: {estimate}
+depends_on: {depends_on}
+{separator}
+{body}
+"#,
+        id = task.id,
+        sprint = task.sprint.as_deref().unwrap_or("-"),
+        description = task.description,
+        status = task.status,
+        due = optional(task.due.map(|due| due.to_string())),
+        recur = optional(task.recur.map(|recur| recur.to_string())),
+        milestone = optional(task.milestone.clone()),
+        estimate = optional(task.estimate.map(estimate_input)),
+        depends_on = depends_on.join(", "),
+        separator = BODY_SEPARATOR,
+        body = task.body.as_deref().unwrap_or(""),
+    )
+}
+
+/// `text` with `error` on top, replacing the error of a previous attempt. The
+/// body is kept as is, as it may hold lines looking like an error
+pub fn with_edit_error(text: &str, error: &Error) -> String {
+    let (fields, body) = match text.split_once(BODY_SEPARATOR) {
+        Some((fields, body)) => (fields, Some(body)),
+        None => (text, None),
+    };
+    let fields: Vec<&str> = fields
+        .lines()
+        .filter(|line| !line.starts_with(EDIT_ERROR_PREFIX))
+        .collect();
+    let mut text = format!("{}{}\n{}", EDIT_ERROR_PREFIX, error, fields.join("\n"));
+    match body {
+        Some(body) => {
+            text.push('\n');
+            text.push_str(BODY_SEPARATOR);
+            text.push_str(body);
+        }
+        None => text.push('\n'),
+    }
+    text
+}
+
+const BODY_SEPARATOR: &str = "--- Body, in Markdown, below this line ---";
+
+const EDIT_ERROR_PREFIX: &str = "# Error: ";
+
+/// Estimates the way they are typed, `Estimate` display adding spaces
+fn estimate_input(estimate: Estimate) -> String {
+    match estimate {
+        Estimate::Time { minutes } => match (minutes / 60, minutes % 60) {
+            (0, minutes) => format!("{}m", minutes),
+            (hours, 0) => format!("{}h", hours),
+            (hours, minutes) => format!("{}h{}m", hours, minutes),
+        },
+        Estimate::Points { points } => format!("{}pt", points),
+    }
+}
+
+/// Opens `text` in `$VISUAL`, `$EDITOR` or vi, and returns it once the editor exits
+pub fn open_in_editor(text: &str) -> Result<String> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or("vi".to_string());
+    let failed = || Error::EditorFailed {
+        editor: editor.clone(),
+    };
+    let (path, mut file) = create_temp_file().map_err(|_| failed())?;
+    file.write_all(text.as_bytes()).map_err(|_| failed())?;
+    drop(file);
+    // The editor may come with arguments, such as `code --wait`
+    let mut words = editor.split_whitespace();
+    let status = Command::new(words.next().ok_or_else(failed)?)
+        .args(words)
+        .arg(&path)
+        .status();
+    let edited = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+    match status {
+        Ok(status) if status.success() => edited.map_err(|_| failed()),
+        _ => Err(failed()),
+    }
+}
+
+/// A new file only readable by the user, with a random name so that it can't
+/// be prepared in advance by someone else sharing the temp directory
+fn create_temp_file() -> std::io::Result<(PathBuf, File)> {
+    loop {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        let path = std::env::temp_dir().join(format!("rtask-edit-{:016x}.txt", hasher.finish()));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        }
+    }
+}
@@ -1,19 +1,154 @@
+use crate::recurrence::MAX_RECURRENCE_DAYS;
+
 pub type Result<T> = core::result::Result<T, Error>;
 
 #[derive(Debug)]
 pub enum Error {
     HomePathNotFound,
-    UnknownCommand { command: String },
+    UnknownCommand {
+        command: String,
+    },
     NoCommand,
     UnknownError,
-    TooManyArguments { max: u8, got: u8 },
-    WrongNumberOfArguments { expected: u8, got: u8 },
+    TooManyArguments {
+        max: u8,
+        got: u8,
+    },
+    WrongNumberOfArguments {
+        expected: u8,
+        got: u8,
+    },
     EmptyDescription,
-    IdMustBeNumber { id: String },
+    EmptySearchQuery,
+    EmptyAnnotation,
+    EmptyChecklistItem,
+    ChecklistItemMustBeNumber {
+        item: String,
+    },
+    ChecklistItemNotFound {
+        id: String,
+        number: usize,
+    },
+    IdMustBeNumber {
+        id: String,
+    },
+    InvalidIdRange {
+        range: String,
+    },
+    IdListTooLong {
+        list: String,
+        max: usize,
+    },
     FailedToPersistChanges,
     FailedToAccessPersistedData,
-    UnknownStatus { status: String },
-    TaskNotFound { id: String },
+    UnknownStatus {
+        status: String,
+    },
+    TaskNotFound {
+        id: String,
+    },
+    FailedToLoadConfig,
+    MissingOption {
+        option: String,
+    },
+    ConflictingOptions {
+        option: String,
+        other: String,
+    },
+    UnknownOption {
+        option: String,
+    },
+    DependencyCycle {
+        id: String,
+        on: String,
+    },
+    TaskBlocked {
+        id: String,
+        blocked_by: String,
+    },
+    MissingOptionValue {
+        option: String,
+    },
+    InvalidDate {
+        date: String,
+    },
+    InvalidRecurrence {
+        rule: String,
+    },
+    NoNextOccurrence {
+        rule: String,
+        from: String,
+    },
+    TaskNotRecurring {
+        id: String,
+    },
+    InvalidConfig {
+        reason: String,
+    },
+    IllegalTransition {
+        from: String,
+        to: String,
+        allowed: String,
+    },
+    InvalidDuration {
+        duration: String,
+    },
+    TimerAlreadyRunning {
+        id: String,
+    },
+    NoRunningTimer,
+    InvalidEstimate {
+        estimate: String,
+    },
+    MilestoneAlreadyExists {
+        name: String,
+    },
+    MilestoneNotFound {
+        name: String,
+    },
+    SprintAlreadyExists {
+        name: String,
+    },
+    SprintNotFound {
+        name: String,
+    },
+    SprintClosed {
+        name: String,
+    },
+    NoActiveSprint,
+    NoNextSprint {
+        name: String,
+    },
+    InvalidSprintDates,
+    InvalidOptionValue {
+        option: String,
+        value: String,
+    },
+    UnsupportedExportFormat {
+        path: String,
+    },
+    FailedToExportChart {
+        path: String,
+    },
+    NotEnoughHistory {
+        weeks: u32,
+    },
+    InvalidEdit {
+        line: String,
+        message: String,
+    },
+    EditorFailed {
+        editor: String,
+    },
+    InvalidFilter {
+        query: String,
+        column: usize,
+        message: String,
+    },
+    InvalidSortKey {
+        key: String,
+        expected: String,
+    },
 }
 
 impl std::error::Error for Error {}
@@ -40,13 +175,144 @@ impl std::fmt::Display for Error {
                 )
             }
             Error::EmptyDescription => write!(f, "Task description cannot be empty"),
+            Error::EmptySearchQuery => write!(f, "Search query cannot be empty"),
+            Error::EmptyAnnotation => write!(f, "Annotation cannot be empty"),
+            Error::EmptyChecklistItem => write!(f, "Checklist item cannot be empty"),
+            Error::ChecklistItemMustBeNumber { item } => {
+                write!(f, "Expected checklist item number, got {}", item)
+            }
+            Error::ChecklistItemNotFound { id, number } => {
+                write!(f, "Task {} has no checklist item {}", id, number)
+            }
             Error::IdMustBeNumber { id } => {
                 write!(f, "Expected numeric id, got {}", id)
             }
+            Error::InvalidIdRange { range } => {
+                write!(f, "Invalid id range {}: expected <first>-<last>", range)
+            }
+            Error::IdListTooLong { list, max } => {
+                write!(f, "Id list {} selects more than {} tasks", list, max)
+            }
             Error::FailedToPersistChanges => write!(f, "Could not persist changes"),
             Error::FailedToAccessPersistedData => write!(f, "Could not access persisted data"),
             Error::UnknownStatus { status } => write!(f, "Unknown status: {}", status),
             Error::TaskNotFound { id } => write!(f, "Task not found. Id: {}", id),
+            Error::FailedToLoadConfig => write!(f, "Could not load config file"),
+            Error::MissingOption { option } => write!(f, "Missing option {}", option),
+            Error::ConflictingOptions { option, other } => {
+                write!(f, "Option {} cannot be combined with {}", option, other)
+            }
+            Error::UnknownOption { option } => write!(f, "Unknown option {}", option),
+            Error::DependencyCycle { id, on } => {
+                write!(
+                    f,
+                    "Task {} cannot depend on task {}: it would create a dependency cycle",
+                    id, on
+                )
+            }
+            Error::TaskBlocked { id, blocked_by } => {
+                write!(
+                    f,
+                    "Task {} is blocked by unfinished tasks: {}",
+                    id, blocked_by
+                )
+            }
+            Error::MissingOptionValue { option } => write!(f, "Missing value for {}", option),
+            Error::InvalidDate { date } => {
+                write!(
+                    f,
+                    "Invalid date {}: expected YYYY-MM-DD, today, tomorrow, yesterday or a weekday",
+                    date
+                )
+            }
+            Error::InvalidRecurrence { rule } => {
+                write!(
+                    f,
+                    "Invalid recurrence {}: expected daily, weekly, weekdays, monthly, monthly:<day> or every:<days>, up to every:{}",
+                    rule, MAX_RECURRENCE_DAYS
+                )
+            }
+            Error::NoNextOccurrence { rule, from } => {
+                write!(f, "No date follows {} when repeating {}", from, rule)
+            }
+            Error::TaskNotRecurring { id } => write!(f, "Task {} is not recurring", id),
+            Error::InvalidConfig { reason } => write!(f, "Invalid config: {}", reason),
+            Error::InvalidDuration { duration } => {
+                write!(
+                    f,
+                    "Invalid duration {}: expected hours and minutes such as 1h30m",
+                    duration
+                )
+            }
+            Error::TimerAlreadyRunning { id } => {
+                write!(f, "A timer is already running on task {}", id)
+            }
+            Error::NoRunningTimer => write!(f, "No timer is running"),
+            Error::MilestoneAlreadyExists { name } => {
+                write!(f, "Milestone {} already exists", name)
+            }
+            Error::MilestoneNotFound { name } => write!(f, "Milestone not found: {}", name),
+            Error::SprintAlreadyExists { name } => write!(f, "Sprint {} already exists", name),
+            Error::SprintNotFound { name } => write!(f, "Sprint not found: {}", name),
+            Error::SprintClosed { name } => write!(f, "Sprint {} is already closed", name),
+            Error::NoActiveSprint => write!(f, "No sprint is active today"),
+            Error::NoNextSprint { name } => write!(
+                f,
+                "Sprint {} has unfinished tasks and no later sprint to carry them over to. Add one with `rtask sprint add`",
+                name
+            ),
+            Error::InvalidSprintDates => write!(f, "Sprint cannot end before it starts"),
+            Error::InvalidOptionValue { option, value } => {
+                write!(f, "Invalid value for {}: {}", option, value)
+            }
+            Error::UnsupportedExportFormat { path } => {
+                write!(f, "Cannot export to {}, use a .csv or .svg file", path)
+            }
+            Error::FailedToExportChart { path } => write!(f, "Could not write chart to {}", path),
+            Error::InvalidFilter {
+                query,
+                column,
+                message,
+            } => write!(
+                f,
+                "Invalid filter at column {}: {}\n  {}\n  {}^",
+                column,
+                message,
+                query,
+                " ".repeat(column.saturating_sub(1))
+            ),
+            Error::InvalidSortKey { key, expected } => write!(
+                f,
+                "Invalid sort key: {}. Sort keys are {}, optionally followed by + or -",
+                key, expected
+            ),
+            Error::InvalidEdit { line, message } => write!(f, "{} in `{}`", message, line),
+            Error::EditorFailed { editor } => {
+                write!(f, "Could not edit the task with {}", editor)
+            }
+            Error::NotEnoughHistory { weeks } => write!(
+                f,
+                "Not enough history to forecast: no task was completed in the last {} weeks",
+                weeks
+            ),
+            Error::InvalidEstimate { estimate } => {
+                write!(
+                    f,
+                    "Invalid estimate {}: expected a duration such as 3h or up to 1000 points such as 5pt",
+                    estimate
+                )
+            }
+            Error::IllegalTransition { from, to, allowed } => {
+                let allowed = match allowed.is_empty() {
+                    true => "none",
+                    false => allowed,
+                };
+                write!(
+                    f,
+                    "Cannot move task from {} to {}. Valid next states: {}",
+                    from, to, allowed
+                )
+            }
         }
     }
 }
@@ -1,241 +1,3124 @@
+use std::collections::HashSet;
+
+use chrono::{Days, Local, NaiveTime, TimeDelta};
+
 use crate::{
+    chart::{burndown, export_chart, throughput, ChartSeries},
     command::{
-        CommandAdd, CommandDelete, CommandList, CommandMarkDone, CommandMarkInProgress,
-        CommandUpdate,
+        CommandAdd, CommandAnnotate, CommandChartBurndown, CommandChartThroughput, CommandCheckAdd,
+        CommandCheckDone, CommandDelete, CommandDepend, CommandEdit, CommandEstimate,
+        CommandForecast, CommandHeatmap, CommandList, CommandLog, CommandLogTime,
+        CommandMilestoneAdd, CommandMilestoneAssign, CommandRecurStop, CommandSearch,
+        CommandSetStatus, CommandShow, CommandSprintAdd, CommandSprintClose, CommandSprintPlan,
+        CommandStandup, CommandStart, CommandStats, CommandTimesheet, CommandUpdate, TaskSelector,
     },
+    config::{BlockedTaskPolicy, Config},
     db::TaskStorage,
+    dependency,
+    edit::{render_task_edit, with_edit_error, TaskEdit},
+    forecast::{forecast, weekly_samples, Forecast, Rng},
+    heatmap::{build_heatmap, Heatmap},
+    journal::{build_journal, JournalDay},
+    milestone::{milestone_progress, Milestone, MilestoneProgress},
+    query::TaskQuery,
+    recurrence::RecurrenceRule,
+    report::{build_estimate_report, EstimateReport},
+    search::{highlights, searchable_text, SearchResult},
+    sprint::{total_estimate, Sprint, SprintBoard, SprintCloseReport, SprintPlan},
+    standup::{build_standup, previous_working_day, Standup},
+    stats::{build_stats, Stats},
     task::{Task, TaskId, TaskStatus},
+    timesheet::{build_timesheet, Timesheet},
     Error, Result,
 };
 
+const SPRINT_LENGTH_IN_DAYS: u64 = 14;
+
 pub struct UpdateStatusResult {
     pub task_id: TaskId,
     pub old_status: TaskStatus,
     pub new_status: TaskStatus,
+    pub blocked_by: Vec<TaskId>,
+    pub next_occurrence: Option<Task>,
+}
+
+pub struct StartTimerResult {
+    pub task: Task,
+    pub status_change: Option<UpdateStatusResult>,
+}
+
+pub struct EditResult {
+    pub task: Task,
+    pub status_change: Option<UpdateStatusResult>,
+}
+
+pub struct CheckResult {
+    pub task: Task,
+    pub status_change: Option<UpdateStatusResult>,
+}
+
+pub struct StopTimerResult {
+    pub task: Task,
+    pub elapsed: TimeDelta,
+}
+
+pub struct TaskDetails {
+    pub task: Task,
+    /// Tasks this one depends on
+    pub dependencies: Vec<Task>,
+    /// Tasks depending on this one
+    pub dependents: Vec<Task>,
+}
+
+pub struct RecurringSeries<'a> {
+    pub series: TaskId,
+    pub rule: RecurrenceRule,
+    pub next: &'a Task,
+    pub completed: usize,
 }
 
 pub struct CommandExecutor {
     db: Box<dyn TaskStorage>,
+    config: Config,
 }
 
 impl CommandExecutor {
-    pub fn new(db: Box<dyn TaskStorage>) -> Self {
-        Self { db }
+    pub fn new(db: Box<dyn TaskStorage>, config: Config) -> Self {
+        Self { db, config }
     }
 }
 
 impl CommandExecutor {
     pub fn execute_command_add(&mut self, command: CommandAdd) -> Result<Task> {
-        let id = TaskId::new(1 + self.db.count_tasks())?;
+        if let Some(milestone) = &command.milestone {
+            self.check_milestone_exists(milestone)?;
+        }
+        let id = next_task_id(self.db.get_tasks()?)?;
         let description = command.description;
-        let task = Task::new(id, description);
+        let mut task = Task::new(id, description);
+        task.due = command.due;
+        if command.recur.is_some() {
+            task.series = Some(id);
+            task.recur = command.recur;
+        }
+        task.milestone = command.milestone;
+        task.body = command.body;
         self.db.create_task(task.clone())?;
         Ok(task)
     }
 
-    pub fn execute_command_update(&mut self, command: CommandUpdate) -> Result<Task> {
-        let task = self.db.get_task(&command.id).ok_or(Error::TaskNotFound {
+    pub fn execute_command_update(&mut self, command: CommandUpdate) -> Result<Vec<Task>> {
+        let mut tasks = self.select_tasks(&command.selector)?;
+        for task in tasks.iter_mut() {
+            task.set_description(command.description.clone());
+        }
+        self.transaction(|db| {
+            tasks
+                .iter()
+                .try_for_each(|task| db.update_task(&task.id, task.clone()))
+        })?;
+        Ok(tasks)
+    }
+
+    pub fn execute_command_delete(&mut self, command: CommandDelete) -> Result<Vec<TaskId>> {
+        let ids: Vec<TaskId> = self
+            .select_tasks(&command.selector)?
+            .iter()
+            .map(|task| task.id)
+            .collect();
+        self.transaction(|db| ids.iter().try_for_each(|id| db.delete_task(id)))?;
+        Ok(ids)
+    }
+
+    /// Nothing is written unless every selected task can move to the new status
+    pub fn execute_command_set_status(
+        &mut self,
+        command: CommandSetStatus,
+    ) -> Result<Vec<UpdateStatusResult>> {
+        let mut changes: Vec<(Task, UpdateStatusResult)> = vec![];
+        for mut task in self.select_tasks(&command.selector)? {
+            let result = self.transition_status(&mut task, command.status)?;
+            changes.push((task, result));
+        }
+        self.transaction(|db| {
+            let mut results = vec![];
+            for (task, mut result) in changes {
+                db.update_task(&task.id.clone(), task)?;
+                if let Some(next_task) = result.next_occurrence.as_mut() {
+                    next_task.id = next_task_id(db.get_tasks()?)?;
+                    db.create_task(next_task.clone())?;
+                }
+                results.push(result);
+            }
+            Ok(results)
+        })
+    }
+
+    pub fn execute_command_list(&self, command: CommandList) -> Result<Vec<&Task>> {
+        self.db.query(&TaskQuery {
+            ids: None,
+            status: command.status,
+            filter: command.filter,
+            dependency: command.dependency,
+            sort: Some(
+                command
+                    .sort
+                    .unwrap_or_else(|| self.config.default_sort.clone()),
+            ),
+            reverse: command.reverse,
+            offset: command.offset,
+            limit: command.limit,
+        })
+    }
+
+    pub fn execute_command_depend(&mut self, command: CommandDepend) -> Result<Task> {
+        if self.db.get_task(&command.on)?.is_none() {
+            return Err(Error::TaskNotFound {
+                id: command.on.to_string(),
+            });
+        }
+        if dependency::creates_cycle(self.db.get_tasks()?, &command.id, &command.on) {
+            return Err(Error::DependencyCycle {
+                id: command.id.to_string(),
+                on: command.on.to_string(),
+            });
+        }
+        let task = self.db.get_task(&command.id)?.ok_or(Error::TaskNotFound {
             id: command.id.to_string(),
         })?;
         let mut updated_task = task.clone();
-        updated_task.set_description(command.description);
+        updated_task.add_dependency(command.on);
         self.db.update_task(&command.id, updated_task.clone())?;
         Ok(updated_task)
     }
 
-    pub fn execute_command_delete(&mut self, command: CommandDelete) -> Result<TaskId> {
-        self.db.delete_task(&command.id)?;
-        Ok(command.id)
+    /// The ready task to work on next. Without priorities, tasks in progress
+    /// come first, then those unblocking the most tasks, then the oldest
+    pub fn execute_command_next(&self) -> Result<Option<&Task>> {
+        let tasks = self.db.get_tasks()?;
+        let next = tasks
+            .iter()
+            .filter(|task| dependency::is_ready(task, tasks))
+            .min_by_key(|task| {
+                (
+                    task.status != TaskStatus::InProgress,
+                    std::cmp::Reverse(dependency::count_dependents(task, tasks)),
+                    task.created_at,
+                    task.id,
+                )
+            });
+        Ok(next)
+    }
+
+    pub fn execute_command_show(&self, command: CommandShow) -> Result<TaskDetails> {
+        let task = self
+            .db
+            .get_task(&command.id)?
+            .cloned()
+            .ok_or(Error::TaskNotFound {
+                id: command.id.to_string(),
+            })?;
+        let dependencies = self
+            .db
+            .query(&TaskQuery::ids(task.depends_on.clone()))?
+            .into_iter()
+            .cloned()
+            .collect();
+        let dependents = self
+            .db
+            .get_tasks()?
+            .iter()
+            .filter(|other| other.depends_on.contains(&task.id))
+            .cloned()
+            .collect();
+        Ok(TaskDetails {
+            task,
+            dependencies,
+            dependents,
+        })
     }
 
-    pub fn execute_command_mark_in_progress(
+    /// `edit` is given the text to edit, e.g. in `$EDITOR`, and returns it
+    /// edited. Invalid edits are given back with the error on top, until they
+    /// are valid or cancelled. `None` when nothing changed
+    pub fn execute_command_edit(
         &mut self,
-        command: CommandMarkInProgress,
-    ) -> Result<UpdateStatusResult> {
-        let task = self.db.get_task(&command.id).ok_or(Error::TaskNotFound {
+        command: CommandEdit,
+        edit: &mut dyn FnMut(&str) -> Result<String>,
+    ) -> Result<Option<EditResult>> {
+        let task = self
+            .db
+            .get_task(&command.id)?
+            .cloned()
+            .ok_or(Error::TaskNotFound {
+                id: command.id.to_string(),
+            })?;
+        let today = Local::now().date_naive();
+        let mut text = render_task_edit(&task);
+        let (updated_task, mut status_change) = loop {
+            let edited = edit(&text)?;
+            let changes = match TaskEdit::parse(&edited, today) {
+                Ok(None) => return Ok(None),
+                Ok(Some(changes)) if changes == TaskEdit::from_task(&task) => return Ok(None),
+                Ok(Some(changes)) => changes,
+                Err(error) => {
+                    text = with_edit_error(&edited, &error);
+                    continue;
+                }
+            };
+            match self.apply_task_edit(&task, changes) {
+                Ok(result) => break result,
+                Err(error) => text = with_edit_error(&edited, &error),
+            }
+        };
+        self.save_status_change(&updated_task, &mut status_change)?;
+        Ok(Some(EditResult {
+            task: updated_task,
+            status_change,
+        }))
+    }
+
+    pub fn execute_command_annotate(&mut self, command: CommandAnnotate) -> Result<Task> {
+        let mut task = self
+            .db
+            .get_task(&command.id)?
+            .cloned()
+            .ok_or(Error::TaskNotFound {
+                id: command.id.to_string(),
+            })?;
+        task.annotate(&command.text)?;
+        self.db.update_task(&command.id, task.clone())?;
+        Ok(task)
+    }
+
+    pub fn execute_command_check_add(&mut self, command: CommandCheckAdd) -> Result<Task> {
+        let mut task = self
+            .db
+            .get_task(&command.id)?
+            .cloned()
+            .ok_or(Error::TaskNotFound {
+                id: command.id.to_string(),
+            })?;
+        task.add_checklist_item(&command.text)?;
+        self.db.update_task(&command.id, task.clone())?;
+        Ok(task)
+    }
+
+    /// Marks the task done too when it was the last open item, if configured
+    /// and allowed by the workflow
+    pub fn execute_command_check_done(&mut self, command: CommandCheckDone) -> Result<CheckResult> {
+        let mut task = self
+            .db
+            .get_task(&command.id)?
+            .cloned()
+            .ok_or(Error::TaskNotFound {
+                id: command.id.to_string(),
+            })?;
+        task.check_item(command.number)?;
+        let complete = task
+            .checklist_progress()
+            .is_some_and(|(done, total)| done == total);
+        let mut status_change = match complete
+            && self.config.auto_complete_checklists
+            && self
+                .config
+                .workflow
+                .allowed_transitions(task.status)
+                .contains(&TaskStatus::Done)
+        {
+            true => Some(self.transition_status(&mut task, TaskStatus::Done)?),
+            false => None,
+        };
+        self.save_status_change(&task, &mut status_change)?;
+        Ok(CheckResult {
+            task,
+            status_change,
+        })
+    }
+
+    pub fn execute_command_start(&mut self, command: CommandStart) -> Result<StartTimerResult> {
+        if let Some(running) = self
+            .db
+            .get_tasks()?
+            .iter()
+            .find(|task| task.running_time_entry().is_some())
+        {
+            return Err(Error::TimerAlreadyRunning {
+                id: running.id.to_string(),
+            });
+        }
+        let mut task = self
+            .db
+            .get_task(&command.id)?
+            .cloned()
+            .ok_or(Error::TaskNotFound {
+                id: command.id.to_string(),
+            })?;
+        let status_change = match task.status {
+            TaskStatus::InProgress => None,
+            _ => Some(self.transition_status(&mut task, TaskStatus::InProgress)?),
+        };
+        task.start_timer();
+        self.db.update_task(&command.id, task.clone())?;
+        Ok(StartTimerResult {
+            task,
+            status_change,
+        })
+    }
+
+    pub fn execute_command_stop(&mut self) -> Result<StopTimerResult> {
+        let mut task = self
+            .db
+            .get_tasks()?
+            .iter()
+            .find(|task| task.running_time_entry().is_some())
+            .cloned()
+            .ok_or(Error::NoRunningTimer)?;
+        let elapsed = task.stop_timer().ok_or(Error::NoRunningTimer)?;
+        self.db.update_task(&task.id.clone(), task.clone())?;
+        Ok(StopTimerResult { task, elapsed })
+    }
+
+    pub fn execute_command_log_time(&mut self, command: CommandLogTime) -> Result<Task> {
+        let task = self.db.get_task(&command.id)?.ok_or(Error::TaskNotFound {
             id: command.id.to_string(),
         })?;
-        let task_id = task.id;
-        let old_status = task.status;
+        let start = match command.on {
+            Some(on) => on
+                .and_time(NaiveTime::MIN)
+                .and_local_timezone(Local)
+                .earliest()
+                .ok_or(Error::InvalidDate {
+                    date: on.to_string(),
+                })?,
+            None => Local::now() - command.duration,
+        };
         let mut updated_task = task.clone();
-        updated_task.set_status(TaskStatus::InProgress);
-        self.db.update_task(&command.id, updated_task)?;
-        Ok(UpdateStatusResult {
-            task_id,
-            old_status,
-            new_status: TaskStatus::InProgress,
-        })
+        updated_task.log_time(start, command.duration);
+        self.db.update_task(&command.id, updated_task.clone())?;
+        Ok(updated_task)
     }
 
-    pub fn execute_command_mark_done(
-        &mut self,
-        command: CommandMarkDone,
-    ) -> Result<UpdateStatusResult> {
-        let task = self.db.get_task(&command.id).ok_or(Error::TaskNotFound {
+    pub fn execute_command_timesheet(&self, command: CommandTimesheet) -> Result<Timesheet> {
+        Ok(build_timesheet(
+            self.db.get_tasks()?,
+            command.from,
+            command.to,
+            command.grouping,
+            &self.config.timesheet_rounding,
+        ))
+    }
+
+    pub fn execute_command_estimate(&mut self, command: CommandEstimate) -> Result<Task> {
+        let task = self.db.get_task(&command.id)?.ok_or(Error::TaskNotFound {
             id: command.id.to_string(),
         })?;
-        let task_id = task.id;
-        let old_status = task.status;
         let mut updated_task = task.clone();
-        updated_task.set_status(TaskStatus::Done);
-        self.db.update_task(&command.id, updated_task)?;
-        Ok(UpdateStatusResult {
-            task_id,
-            old_status,
-            new_status: TaskStatus::Done,
-        })
+        updated_task.set_estimate(Some(command.estimate));
+        self.db.update_task(&command.id, updated_task.clone())?;
+        Ok(updated_task)
     }
 
-    pub fn execute_command_list(&self, command: CommandList) -> Result<Vec<&Task>> {
-        let tasks: Vec<&Task> = self
+    pub fn execute_command_report_estimates(&self) -> Result<EstimateReport<'_>> {
+        Ok(build_estimate_report(self.db.get_tasks()?))
+    }
+
+    pub fn execute_command_milestone_add(
+        &mut self,
+        command: CommandMilestoneAdd,
+    ) -> Result<Milestone> {
+        if self
             .db
-            .get_tasks()
+            .get_milestones()?
             .iter()
-            .filter(|task| {
-                command
-                    .status
-                    .as_ref()
-                    .is_none_or(|status| *status == task.status)
-            })
-            .collect();
-        Ok(tasks)
+            .any(|milestone| milestone.name == command.name)
+        {
+            return Err(Error::MilestoneAlreadyExists { name: command.name });
+        }
+        let milestone = Milestone::new(command.name, command.target);
+        self.db.create_milestone(milestone.clone())?;
+        Ok(milestone)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::task::TaskDescription;
+    pub fn execute_command_milestone_assign(
+        &mut self,
+        command: CommandMilestoneAssign,
+    ) -> Result<Task> {
+        self.check_milestone_exists(&command.name)?;
+        let task = self.db.get_task(&command.id)?.ok_or(Error::TaskNotFound {
+            id: command.id.to_string(),
+        })?;
+        let mut updated_task = task.clone();
+        updated_task.set_milestone(Some(command.name));
+        self.db.update_task(&command.id, updated_task.clone())?;
+        Ok(updated_task)
+    }
 
-    use super::*;
+    pub fn execute_command_roadmap(&self) -> Result<Vec<MilestoneProgress<'_>>> {
+        let today = Local::now().date_naive();
+        let mut milestones: Vec<&Milestone> = self.db.get_milestones()?.iter().collect();
+        milestones.sort_by_key(|milestone| (milestone.target.is_none(), milestone.target));
+        let tasks = self.db.get_tasks()?;
+        Ok(milestones
+            .into_iter()
+            .map(|milestone| milestone_progress(milestone, tasks, today))
+            .collect())
+    }
 
-    struct MockDb {
-        tasks: Vec<Task>,
+    pub fn execute_command_sprint_add(&mut self, command: CommandSprintAdd) -> Result<Sprint> {
+        if self
+            .db
+            .get_sprints()?
+            .iter()
+            .any(|sprint| sprint.name == command.name)
+        {
+            return Err(Error::SprintAlreadyExists { name: command.name });
+        }
+        let end = command
+            .end
+            .unwrap_or(command.start + Days::new(SPRINT_LENGTH_IN_DAYS - 1));
+        if end < command.start {
+            return Err(Error::InvalidSprintDates);
+        }
+        let sprint = Sprint::new(command.name, command.start, end, command.capacity);
+        self.db.create_sprint(sprint.clone())?;
+        Ok(sprint)
     }
-    impl MockDb {
-        fn new() -> Self {
-            Self { tasks: vec![] }
+
+    pub fn execute_command_sprint_plan(
+        &mut self,
+        command: CommandSprintPlan,
+    ) -> Result<SprintPlan> {
+        let mut sprint = self.get_sprint(&command.name)?;
+        if sprint.closed {
+            return Err(Error::SprintClosed { name: sprint.name });
+        }
+        let mut tasks: Vec<Task> = vec![];
+        for id in &command.ids {
+            let task = self
+                .db
+                .get_task(id)?
+                .ok_or(Error::TaskNotFound { id: id.to_string() })?;
+            tasks.push(task.clone());
         }
+        // Tasks moved from another open sprint are no longer committed to it
+        let mut previous_sprints: Vec<Sprint> = self
+            .db
+            .get_sprints()?
+            .iter()
+            .filter(|previous| !previous.closed && previous.name != sprint.name)
+            .filter(|previous| {
+                tasks
+                    .iter()
+                    .any(|task| previous.committed.contains(&task.id))
+            })
+            .cloned()
+            .collect();
+        self.transaction(|db| {
+            for previous in previous_sprints.iter_mut() {
+                tasks.iter().for_each(|task| previous.uncommit(&task.id));
+                db.update_sprint(&previous.name.clone(), previous.clone())?;
+            }
+            for mut task in tasks {
+                task.set_sprint(Some(sprint.name.clone()));
+                sprint.commit(task.id);
+                db.update_task(&task.id.clone(), task)?;
+            }
+            db.update_sprint(&command.name, sprint.clone())
+        })?;
+        let load = total_estimate(
+            self.db
+                .get_tasks()?
+                .iter()
+                .filter(|task| task.sprint.as_ref() == Some(&sprint.name)),
+        );
+        Ok(SprintPlan {
+            sprint,
+            planned: command.ids,
+            load,
+        })
     }
-    impl TaskStorage for MockDb {
-        fn create_task(&mut self, task: Task) -> Result<()> {
-            self.tasks.push(task);
-            Ok(())
+
+    pub fn execute_command_sprint_current(&self) -> Result<SprintBoard<'_>> {
+        let today = Local::now().date_naive();
+        let sprint = self
+            .db
+            .get_sprints()?
+            .iter()
+            .find(|sprint| sprint.is_active(today))
+            .cloned()
+            .ok_or(Error::NoActiveSprint)?;
+        let tasks: Vec<&Task> = self
+            .db
+            .get_tasks()?
+            .iter()
+            .filter(|task| task.sprint.as_ref() == Some(&sprint.name))
+            .collect();
+        let mut columns: Vec<(TaskStatus, Vec<&Task>)> = self
+            .config
+            .workflow
+            .statuses
+            .iter()
+            .map(|status| (*status, vec![]))
+            .collect();
+        for task in tasks {
+            match columns
+                .iter_mut()
+                .find(|(status, _)| *status == task.status)
+            {
+                Some((_, column)) => column.push(task),
+                None => columns.push((task.status, vec![task])),
+            }
         }
+        Ok(SprintBoard { sprint, columns })
+    }
 
-        fn count_tasks(&self) -> u32 {
-            self.tasks.len() as u32
+    pub fn execute_command_sprint_close(
+        &mut self,
+        command: CommandSprintClose,
+    ) -> Result<SprintCloseReport> {
+        let today = Local::now().date_naive();
+        let mut sprint = match command.name {
+            Some(name) => self.get_sprint(&name)?,
+            None => self
+                .db
+                .get_sprints()?
+                .iter()
+                .find(|sprint| sprint.is_active(today))
+                .cloned()
+                .ok_or(Error::NoActiveSprint)?,
+        };
+        if sprint.closed {
+            return Err(Error::SprintClosed { name: sprint.name });
+        }
+        let tasks = self.db.get_tasks()?;
+        let committed: Vec<&Task> = tasks
+            .iter()
+            .filter(|task| sprint.committed.contains(&task.id))
+            .collect();
+        let completed: Vec<&Task> = committed
+            .iter()
+            .filter(|task| task.status == TaskStatus::Done)
+            .copied()
+            .collect();
+        let committed_estimate = total_estimate(committed.iter().copied());
+        let completed_estimate = total_estimate(completed.iter().copied());
+        let (committed, completed) = (committed.len(), completed.len());
+        let unfinished: Vec<Task> = tasks
+            .iter()
+            .filter(|task| task.sprint.as_ref() == Some(&sprint.name) && !task.status.is_closed())
+            .cloned()
+            .collect();
+        let mut next_sprint = self
+            .db
+            .get_sprints()?
+            .iter()
+            .filter(|next| !next.closed && next.name != sprint.name && next.start > sprint.start)
+            .min_by_key(|next| next.start)
+            .cloned();
+        if !unfinished.is_empty() && next_sprint.is_none() {
+            return Err(Error::NoNextSprint { name: sprint.name });
         }
+        let carried_over: Vec<TaskId> = unfinished.iter().map(|task| task.id).collect();
+        sprint.closed = true;
+        self.transaction(|db| {
+            for mut task in unfinished {
+                task.set_sprint(next_sprint.as_ref().map(|next| next.name.clone()));
+                if let Some(next) = next_sprint.as_mut() {
+                    next.commit(task.id);
+                }
+                db.update_task(&task.id.clone(), task)?;
+            }
+            if let Some(next) = &next_sprint {
+                db.update_sprint(&next.name.clone(), next.clone())?;
+            }
+            db.update_sprint(&sprint.name.clone(), sprint.clone())
+        })?;
+        Ok(SprintCloseReport {
+            sprint,
+            committed,
+            completed,
+            committed_estimate,
+            completed_estimate,
+            carried_over,
+            next_sprint: next_sprint.map(|next| next.name),
+        })
+    }
 
-        fn get_task(&mut self, id: &TaskId) -> Option<&Task> {
-            self.tasks.iter().find(|task| task.id == *id)
+    pub fn execute_command_chart_burndown(
+        &self,
+        command: CommandChartBurndown,
+    ) -> Result<ChartSeries> {
+        let milestone = self
+            .db
+            .get_milestones()?
+            .iter()
+            .find(|milestone| milestone.name == command.milestone)
+            .ok_or(Error::MilestoneNotFound {
+                name: command.milestone,
+            })?;
+        let series = burndown(milestone, self.db.get_tasks()?, Local::now().date_naive());
+        if let Some(path) = command.export {
+            export_chart(&series, &path)?;
         }
+        Ok(series)
+    }
 
-        fn get_tasks(&self) -> &Vec<Task> {
-            &self.tasks
+    pub fn execute_command_chart_throughput(
+        &self,
+        command: CommandChartThroughput,
+    ) -> Result<ChartSeries> {
+        let series = throughput(
+            self.db.get_tasks()?,
+            command.weeks,
+            Local::now().date_naive(),
+        );
+        if let Some(path) = command.export {
+            export_chart(&series, &path)?;
         }
+        Ok(series)
+    }
 
-        fn update_task(&mut self, id: &TaskId, task: Task) -> Result<()> {
-            if let Some(t) = self.tasks.iter_mut().find(|task| task.id == *id) {
-                *t = task;
+    pub fn execute_command_forecast(&self, command: CommandForecast) -> Result<Forecast> {
+        if let Some(name) = &command.milestone {
+            self.check_milestone_exists(name)?;
+        }
+        if let Some(name) = &command.sprint {
+            self.get_sprint(name)?;
+        }
+        let filtered = command.filter.is_some();
+        let remaining = self
+            .db
+            .query(&TaskQuery {
+                filter: command.filter,
+                ..Default::default()
+            })?
+            .into_iter()
+            .filter(|task| !task.status.is_closed())
+            .filter(|task| command.milestone.is_none() || task.milestone == command.milestone)
+            .filter(|task| command.sprint.is_none() || task.sprint == command.sprint)
+            .count();
+        let mut scope = match (&command.milestone, &command.sprint) {
+            (Some(milestone), Some(sprint)) => {
+                format!("milestone {} in sprint {}", milestone, sprint)
             }
-            Ok(())
+            (Some(milestone), None) => format!("milestone {}", milestone),
+            (None, Some(sprint)) => format!("sprint {}", sprint),
+            (None, None) => "all open tasks".to_string(),
+        };
+        if filtered {
+            scope.push_str(" matching --where");
         }
+        let tasks = self.db.get_tasks()?;
+        let today = Local::now().date_naive();
+        let seed = command
+            .seed
+            .unwrap_or_else(|| Local::now().timestamp_nanos_opt().unwrap_or_default() as u64);
+        forecast(
+            scope,
+            remaining,
+            weekly_samples(tasks, command.weeks, today),
+            today,
+            &mut Rng::new(seed),
+        )
+    }
 
-        fn delete_task(&mut self, id: &TaskId) -> Result<()> {
-            self.tasks = self
-                .tasks
-                .iter()
-                .cloned()
-                .filter(|task| task.id != *id)
-                .collect();
-            Ok(())
-        }
+    pub fn execute_command_stats(&self, command: CommandStats) -> Result<Stats> {
+        Ok(build_stats(
+            self.db.get_tasks()?,
+            &self.config.workflow.statuses,
+            command.weeks,
+            Local::now(),
+        ))
     }
 
-    #[test]
-    fn test_execute_command_add() {
-        let db = Box::new(MockDb::new());
-        let mut command_executor = CommandExecutor::new(db);
-        let command: CommandAdd = CommandAdd {
-            description: TaskDescription::new("walk the dog".to_string()).unwrap(),
-        };
-        let result = command_executor.execute_command_add(command);
-        assert!(result.is_ok());
-        assert_eq!(
-            result.unwrap().description,
-            TaskDescription::new("walk the dog".to_string()).unwrap()
-        );
-        let result = command_executor.execute_command_list(CommandList { status: None });
-        assert!(result.is_ok());
+    pub fn execute_command_heatmap(&self, command: CommandHeatmap) -> Result<Heatmap> {
+        Ok(build_heatmap(
+            self.db.get_tasks()?,
+            command.metric,
+            Local::now().date_naive(),
+        ))
     }
 
-    #[test]
-    fn test_execute_command_delete() {
-        let db = Box::new(MockDb::new());
-        let mut command_executor = CommandExecutor::new(db);
-        let dog_task = command_executor
-            .execute_command_add(CommandAdd {
-                description: TaskDescription::new("walk the dog".to_string()).unwrap(),
-            })
-            .unwrap();
-        let fish_task = command_executor
-            .execute_command_add(CommandAdd {
-                description: TaskDescription::new("feed the fish".to_string()).unwrap(),
-            })
-            .unwrap();
-        command_executor
-            .execute_command_delete(CommandDelete { id: dog_task.id })
-            .unwrap();
-        let tasks = command_executor
-            .execute_command_list(CommandList { status: None })
-            .unwrap();
-        assert_eq!(tasks.len(), 1);
-        assert_eq!(tasks[0].id, fish_task.id);
+    pub fn execute_command_standup(&self, command: CommandStandup) -> Result<Standup<'_>> {
+        let today = Local::now().date_naive();
+        let since = command.since.unwrap_or_else(|| previous_working_day(today));
+        Ok(build_standup(self.db.get_tasks()?, since, today))
     }
 
-    #[test]
-    fn test_execute_command_mark_done() {
-        let db = Box::new(MockDb::new());
-        let mut command_executor = CommandExecutor::new(db);
-        let task = command_executor
-            .execute_command_add(CommandAdd {
-                description: TaskDescription::new("walk the dog".to_string()).unwrap(),
+    pub fn execute_command_log(&self, command: CommandLog) -> Result<Vec<JournalDay<'_>>> {
+        Ok(build_journal(
+            self.db.get_tasks()?,
+            command.since,
+            command.changes,
+        ))
+    }
+
+    pub fn execute_command_search(&self, command: CommandSearch) -> Result<Vec<SearchResult<'_>>> {
+        let hits = self.db.search(&command.query)?;
+        let mut results = vec![];
+        for hit in hits.into_iter().take(command.limit.unwrap_or(usize::MAX)) {
+            if let Some(task) = self.db.get_task(&hit.id)? {
+                results.push(SearchResult {
+                    task,
+                    score: hit.score,
+                    highlights: highlights(&searchable_text(task), &command.query),
+                });
+            }
+        }
+        Ok(results)
+    }
+
+    pub fn execute_command_recur_list(&self) -> Result<Vec<RecurringSeries<'_>>> {
+        let tasks = self.db.get_tasks()?;
+        let series = tasks
+            .iter()
+            .filter_map(|task| match (task.series, task.recur) {
+                (Some(series), Some(rule)) => Some(RecurringSeries {
+                    series,
+                    rule,
+                    next: task,
+                    completed: tasks
+                        .iter()
+                        .filter(|other| {
+                            other.series == Some(series) && other.status == TaskStatus::Done
+                        })
+                        .count(),
+                }),
+                _ => None,
             })
-            .unwrap();
-        assert_eq!(task.status, TaskStatus::Todo);
-        let result = command_executor
-            .execute_command_mark_done(CommandMarkDone { id: task.id })
-            .unwrap();
-        assert_eq!(result.task_id, task.id);
-        assert_eq!(result.old_status, TaskStatus::Todo);
-        assert_eq!(result.new_status, TaskStatus::Done);
-        let tasks = command_executor
-            .execute_command_list(CommandList { status: None })
-            .unwrap();
-        assert_eq!(tasks.len(), 1);
-        assert_eq!(tasks[0].status, TaskStatus::Done);
+            .collect();
+        Ok(series)
     }
 
-    #[test]
-    fn test_execute_command_mark_in_progress() {
-        let db = Box::new(MockDb::new());
-        let mut command_executor = CommandExecutor::new(db);
+    pub fn execute_command_recur_stop(&mut self, command: CommandRecurStop) -> Result<TaskId> {
+        let task = self.db.get_task(&command.id)?.ok_or(Error::TaskNotFound {
+            id: command.id.to_string(),
+        })?;
+        let series = task.series.ok_or(Error::TaskNotRecurring {
+            id: command.id.to_string(),
+        })?;
+        let recurring_tasks: Vec<Task> = self
+            .db
+            .get_tasks()?
+            .iter()
+            .filter(|task| task.series == Some(series) && task.recur.is_some())
+            .cloned()
+            .collect();
+        self.transaction(|db| {
+            recurring_tasks.into_iter().try_for_each(|mut task| {
+                task.set_recurrence(None);
+                db.update_task(&task.id.clone(), task)
+            })
+        })?;
+        Ok(series)
+    }
+}
+
+impl CommandExecutor {
+    /// Runs `changes` in a storage transaction: they are all written at once,
+    /// or none of them if one fails
+    fn transaction<T>(
+        &mut self,
+        changes: impl FnOnce(&mut dyn TaskStorage) -> Result<T>,
+    ) -> Result<T> {
+        self.db.begin();
+        match changes(self.db.as_mut()) {
+            Ok(value) => self.db.commit().map(|_| value),
+            Err(error) => {
+                self.db.rollback();
+                Err(error)
+            }
+        }
+    }
+
+    fn select_tasks(&self, selector: &TaskSelector) -> Result<Vec<Task>> {
+        let (query, ids) = match selector {
+            TaskSelector::Id(id) => (TaskQuery::ids(vec![*id]), vec![*id]),
+            TaskSelector::Ids(ids) => (TaskQuery::ids(ids.clone()), ids.clone()),
+            TaskSelector::Filter(filter) => (TaskQuery::filter(filter.clone()), vec![]),
+        };
+        let tasks = self.db.query(&query)?;
+        let found: HashSet<TaskId> = tasks.iter().map(|task| task.id).collect();
+        let missing: Vec<TaskId> = ids.into_iter().filter(|id| !found.contains(id)).collect();
+        if !missing.is_empty() {
+            return Err(Error::TaskNotFound {
+                id: join_ids(&missing),
+            });
+        }
+        Ok(tasks.into_iter().cloned().collect())
+    }
+
+    fn get_sprint(&self, name: &str) -> Result<Sprint> {
+        self.db
+            .get_sprints()?
+            .iter()
+            .find(|sprint| sprint.name == name)
+            .cloned()
+            .ok_or(Error::SprintNotFound {
+                name: name.to_string(),
+            })
+    }
+
+    fn check_milestone_exists(&self, name: &str) -> Result<()> {
+        match self
+            .db
+            .get_milestones()?
+            .iter()
+            .any(|milestone| milestone.name == name)
+        {
+            true => Ok(()),
+            false => Err(Error::MilestoneNotFound {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// Checks `changes` against the other tasks, milestones and the workflow,
+    /// without persisting them
+    fn apply_task_edit(
+        &self,
+        task: &Task,
+        changes: TaskEdit,
+    ) -> Result<(Task, Option<UpdateStatusResult>)> {
+        if let Some(milestone) = &changes.milestone {
+            self.check_milestone_exists(milestone)?;
+        }
+        // Dependencies kept from before may point to deleted tasks, only new ones are checked
+        for on in changes
+            .depends_on
+            .iter()
+            .filter(|on| !task.depends_on.contains(on))
+        {
+            if self.db.get_task(on)?.is_none() {
+                return Err(Error::TaskNotFound { id: on.to_string() });
+            }
+            if *on == task.id || dependency::creates_cycle(self.db.get_tasks()?, &task.id, on) {
+                return Err(Error::DependencyCycle {
+                    id: task.id.to_string(),
+                    on: on.to_string(),
+                });
+            }
+        }
+        let mut updated_task = task.clone();
+        // A task made recurring starts its own series, as with `add --recur`
+        if changes.recur.is_some() && task.series.is_none() {
+            updated_task.series = Some(task.id);
+        }
+        updated_task.set_description(changes.description);
+        updated_task.set_due(changes.due);
+        updated_task.set_recurrence(changes.recur);
+        updated_task.set_milestone(changes.milestone);
+        updated_task.set_estimate(changes.estimate);
+        updated_task.set_dependencies(changes.depends_on);
+        updated_task.set_body(changes.body);
+        let status_change = match changes.status == task.status {
+            true => None,
+            false => Some(self.transition_status(&mut updated_task, changes.status)?),
+        };
+        Ok((updated_task, status_change))
+    }
+
+    /// Saves `task` along with the next occurrence its status change created
+    fn save_status_change(
+        &mut self,
+        task: &Task,
+        status_change: &mut Option<UpdateStatusResult>,
+    ) -> Result<()> {
+        self.transaction(|db| {
+            db.update_task(&task.id, task.clone())?;
+            if let Some(next_task) = status_change
+                .as_mut()
+                .and_then(|change| change.next_occurrence.as_mut())
+            {
+                next_task.id = next_task_id(db.get_tasks()?)?;
+                db.create_task(next_task.clone())?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Moves `task` to `status` following the workflow rules, without persisting it.
+    /// The next occurrence of a recurring task is returned for the caller to create.
+    fn transition_status(&self, task: &mut Task, status: TaskStatus) -> Result<UpdateStatusResult> {
+        self.config.workflow.check_transition(task.status, status)?;
+        let blocked_by = match status {
+            TaskStatus::InProgress => dependency::blocked_by(task, self.db.get_tasks()?),
+            _ => vec![],
+        };
+        if !blocked_by.is_empty() && self.config.blocked_task_policy == BlockedTaskPolicy::Refuse {
+            return Err(Error::TaskBlocked {
+                id: task.id.to_string(),
+                blocked_by: join_ids(&blocked_by),
+            });
+        }
+        let old_status = task.status;
+        task.set_status(status);
+        let next_occurrence = match (status, task.recur) {
+            (TaskStatus::Done, Some(rule)) => {
+                let mut next_task = Task::new(
+                    next_task_id(self.db.get_tasks()?)?,
+                    task.description.clone(),
+                );
+                let from = task.due.unwrap_or_else(|| Local::now().date_naive());
+                next_task.due = Some(rule.next_due(from)?);
+                next_task.series = task.series.or(Some(task.id));
+                next_task.recur = Some(rule);
+                task.set_recurrence(None);
+                Some(next_task)
+            }
+            _ => None,
+        };
+        Ok(UpdateStatusResult {
+            task_id: task.id,
+            old_status,
+            new_status: status,
+            blocked_by,
+            next_occurrence,
+        })
+    }
+}
+
+fn next_task_id(tasks: &[Task]) -> Result<TaskId> {
+    let max_id = tasks.iter().map(|task| task.id).max();
+    match max_id {
+        Some(id) => TaskId::new(id.value() + 1),
+        None => TaskId::new(1),
+    }
+}
+
+fn join_ids(ids: &[TaskId]) -> String {
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use chrono::{Datelike, NaiveDate};
+
+    use crate::{
+        command::{parse_command, Command, DependencyFilter},
+        config::WorkflowConfig,
+        datetime::{format_relative, week_bounds},
+        db::Db,
+        filter::Filter,
+        heatmap::HeatmapMetric,
+        journal::ChangeKind,
+        milestone::Schedule,
+        search::{SearchHit, SearchIndex},
+        sort::SortOrder,
+        standup::{previous_working_day, StandupFormat},
+        task::{clean_body, Estimate, EstimateTotal, StatusChange, TaskDescription},
+        timesheet::{RoundingConfig, RoundingMode, TimesheetGrouping},
+    };
+
+    use super::*;
+
+    struct MockDb {
+        tasks: Vec<Task>,
+        milestones: Vec<Milestone>,
+        sprints: Vec<Sprint>,
+        snapshot: Option<(Vec<Task>, Vec<Sprint>)>,
+    }
+    impl MockDb {
+        fn new() -> Self {
+            Self {
+                tasks: vec![],
+                milestones: vec![],
+                sprints: vec![],
+                snapshot: None,
+            }
+        }
+    }
+    impl TaskStorage for MockDb {
+        fn create_task(&mut self, task: Task) -> Result<()> {
+            self.tasks.push(task);
+            Ok(())
+        }
+
+        fn get_task(&self, id: &TaskId) -> Result<Option<&Task>> {
+            Ok(self.tasks.iter().find(|task| task.id == *id))
+        }
+
+        fn get_tasks(&self) -> Result<&[Task]> {
+            Ok(&self.tasks)
+        }
+
+        fn query(&self, query: &TaskQuery) -> Result<Vec<&Task>> {
+            let candidates = match &query.ids {
+                Some(ids) => ids
+                    .iter()
+                    .filter_map(|id| self.tasks.iter().find(|task| task.id == *id))
+                    .collect(),
+                None => self.tasks.iter().collect(),
+            };
+            Ok(query.run(candidates, &self.tasks))
+        }
+
+        fn update_task(&mut self, id: &TaskId, task: Task) -> Result<()> {
+            if let Some(t) = self.tasks.iter_mut().find(|task| task.id == *id) {
+                *t = task;
+            }
+            Ok(())
+        }
+
+        fn delete_task(&mut self, id: &TaskId) -> Result<()> {
+            self.tasks = self
+                .tasks
+                .iter()
+                .filter(|task| task.id != *id)
+                .cloned()
+                .collect();
+            Ok(())
+        }
+
+        fn search(&self, query: &str) -> Result<Vec<SearchHit>> {
+            Ok(SearchIndex::new(&self.tasks).search(query))
+        }
+
+        fn create_milestone(&mut self, milestone: Milestone) -> Result<()> {
+            self.milestones.push(milestone);
+            Ok(())
+        }
+
+        fn get_milestones(&self) -> Result<&[Milestone]> {
+            Ok(&self.milestones)
+        }
+
+        fn create_sprint(&mut self, sprint: Sprint) -> Result<()> {
+            self.sprints.push(sprint);
+            Ok(())
+        }
+
+        fn get_sprints(&self) -> Result<&[Sprint]> {
+            Ok(&self.sprints)
+        }
+
+        fn update_sprint(&mut self, name: &str, sprint: Sprint) -> Result<()> {
+            if let Some(s) = self.sprints.iter_mut().find(|sprint| sprint.name == name) {
+                *s = sprint;
+            }
+            Ok(())
+        }
+
+        fn begin(&mut self) {
+            self.snapshot = Some((self.tasks.clone(), self.sprints.clone()));
+        }
+
+        fn commit(&mut self) -> Result<()> {
+            self.snapshot = None;
+            Ok(())
+        }
+
+        fn rollback(&mut self) {
+            if let Some((tasks, sprints)) = self.snapshot.take() {
+                self.tasks = tasks;
+                self.sprints = sprints;
+            }
+        }
+    }
+
+    #[test]
+    fn test_execute_command_add() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let command: CommandAdd = CommandAdd {
+            description: TaskDescription::new("walk the dog".to_string()).unwrap(),
+            due: None,
+            recur: None,
+            milestone: None,
+            body: None,
+        };
+        let result = command_executor.execute_command_add(command);
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap().description,
+            TaskDescription::new("walk the dog".to_string()).unwrap()
+        );
+        let result = command_executor.execute_command_list(CommandList {
+            status: None,
+            dependency: None,
+            filter: None,
+            sort: None,
+            reverse: false,
+            offset: 0,
+            limit: None,
+        });
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_execute_command_delete() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = command_executor
+            .execute_command_add(CommandAdd {
+                description: TaskDescription::new("walk the dog".to_string()).unwrap(),
+                due: None,
+                recur: None,
+                milestone: None,
+                body: None,
+            })
+            .unwrap();
+        let fish_task = command_executor
+            .execute_command_add(CommandAdd {
+                description: TaskDescription::new("feed the fish".to_string()).unwrap(),
+                due: None,
+                recur: None,
+                milestone: None,
+                body: None,
+            })
+            .unwrap();
+        command_executor
+            .execute_command_delete(CommandDelete {
+                selector: TaskSelector::Id(dog_task.id),
+            })
+            .unwrap();
+        let tasks = command_executor
+            .execute_command_list(CommandList {
+                status: None,
+                dependency: None,
+                filter: None,
+                sort: None,
+                reverse: false,
+                offset: 0,
+                limit: None,
+            })
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, fish_task.id);
+    }
+
+    #[test]
+    fn test_execute_command_mark_done() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
         let task = command_executor
             .execute_command_add(CommandAdd {
                 description: TaskDescription::new("walk the dog".to_string()).unwrap(),
+                due: None,
+                recur: None,
+                milestone: None,
+                body: None,
             })
             .unwrap();
         assert_eq!(task.status, TaskStatus::Todo);
         let result = command_executor
-            .execute_command_mark_in_progress(CommandMarkInProgress { id: task.id })
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap()
+            .remove(0);
+        assert_eq!(result.task_id, task.id);
+        assert_eq!(result.old_status, TaskStatus::Todo);
+        assert_eq!(result.new_status, TaskStatus::Done);
+        let tasks = command_executor
+            .execute_command_list(CommandList {
+                status: None,
+                dependency: None,
+                filter: None,
+                sort: None,
+                reverse: false,
+                offset: 0,
+                limit: None,
+            })
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].status, TaskStatus::Done);
+    }
+
+    #[test]
+    fn test_execute_command_mark_in_progress() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let task = command_executor
+            .execute_command_add(CommandAdd {
+                description: TaskDescription::new("walk the dog".to_string()).unwrap(),
+                due: None,
+                recur: None,
+                milestone: None,
+                body: None,
+            })
             .unwrap();
+        assert_eq!(task.status, TaskStatus::Todo);
+        let result = command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(task.id),
+                status: TaskStatus::InProgress,
+            })
+            .unwrap()
+            .remove(0);
         assert_eq!(result.task_id, task.id);
         assert_eq!(result.old_status, TaskStatus::Todo);
         assert_eq!(result.new_status, TaskStatus::InProgress);
         let tasks = command_executor
-            .execute_command_list(CommandList { status: None })
+            .execute_command_list(CommandList {
+                status: None,
+                dependency: None,
+                filter: None,
+                sort: None,
+                reverse: false,
+                offset: 0,
+                limit: None,
+            })
             .unwrap();
         assert_eq!(tasks.len(), 1);
         assert_eq!(tasks[0].status, TaskStatus::InProgress);
     }
+
+    fn add_task(command_executor: &mut CommandExecutor, description: &str) -> Task {
+        command_executor
+            .execute_command_add(CommandAdd {
+                description: TaskDescription::new(description.to_string()).unwrap(),
+                due: None,
+                recur: None,
+                milestone: None,
+                body: None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_execute_command_depend() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let leash_task = add_task(&mut command_executor, "buy a leash");
+        let task = command_executor
+            .execute_command_depend(CommandDepend {
+                id: dog_task.id,
+                on: leash_task.id,
+            })
+            .unwrap();
+        assert_eq!(task.depends_on, vec![leash_task.id]);
+        let blocked = command_executor
+            .execute_command_list(CommandList {
+                status: None,
+                dependency: Some(DependencyFilter::Blocked),
+                filter: None,
+                sort: None,
+                reverse: false,
+                offset: 0,
+                limit: None,
+            })
+            .unwrap();
+        assert_eq!(blocked.len(), 1);
+        assert_eq!(blocked[0].id, dog_task.id);
+        let ready = command_executor
+            .execute_command_list(CommandList {
+                status: None,
+                dependency: Some(DependencyFilter::Ready),
+                filter: None,
+                sort: None,
+                reverse: false,
+                offset: 0,
+                limit: None,
+            })
+            .unwrap();
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].id, leash_task.id);
+    }
+
+    #[test]
+    fn test_execute_command_depend_rejects_cycles() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let first = add_task(&mut command_executor, "first");
+        let second = add_task(&mut command_executor, "second");
+        let third = add_task(&mut command_executor, "third");
+        command_executor
+            .execute_command_depend(CommandDepend {
+                id: second.id,
+                on: first.id,
+            })
+            .unwrap();
+        command_executor
+            .execute_command_depend(CommandDepend {
+                id: third.id,
+                on: second.id,
+            })
+            .unwrap();
+        let result = command_executor.execute_command_depend(CommandDepend {
+            id: first.id,
+            on: third.id,
+        });
+        assert!(matches!(result, Err(Error::DependencyCycle { .. })));
+        let result = command_executor.execute_command_depend(CommandDepend {
+            id: first.id,
+            on: first.id,
+        });
+        assert!(matches!(result, Err(Error::DependencyCycle { .. })));
+    }
+
+    #[test]
+    fn test_execute_command_mark_in_progress_blocked_task() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let leash_task = add_task(&mut command_executor, "buy a leash");
+        command_executor
+            .execute_command_depend(CommandDepend {
+                id: dog_task.id,
+                on: leash_task.id,
+            })
+            .unwrap();
+        let result = command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(dog_task.id),
+                status: TaskStatus::InProgress,
+            })
+            .unwrap()
+            .remove(0);
+        assert_eq!(result.blocked_by, vec![leash_task.id]);
+
+        let db = Box::new(MockDb::new());
+        let config = Config {
+            blocked_task_policy: BlockedTaskPolicy::Refuse,
+            ..Default::default()
+        };
+        let mut command_executor = CommandExecutor::new(db, config);
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let leash_task = add_task(&mut command_executor, "buy a leash");
+        command_executor
+            .execute_command_depend(CommandDepend {
+                id: dog_task.id,
+                on: leash_task.id,
+            })
+            .unwrap();
+        let result = command_executor.execute_command_set_status(CommandSetStatus {
+            selector: TaskSelector::Id(dog_task.id),
+            status: TaskStatus::InProgress,
+        });
+        assert!(matches!(result, Err(Error::TaskBlocked { .. })));
+        command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(leash_task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap()
+            .remove(0);
+        let result = command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(dog_task.id),
+                status: TaskStatus::InProgress,
+            })
+            .unwrap()
+            .remove(0);
+        assert!(result.blocked_by.is_empty());
+    }
+
+    #[test]
+    fn test_execute_command_next() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let fish_task = add_task(&mut command_executor, "feed the fish");
+        let leash_task = add_task(&mut command_executor, "buy a leash");
+        command_executor
+            .execute_command_depend(CommandDepend {
+                id: dog_task.id,
+                on: leash_task.id,
+            })
+            .unwrap();
+        let next = command_executor.execute_command_next().unwrap().unwrap();
+        assert_eq!(next.id, leash_task.id);
+        command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(leash_task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap();
+        command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(fish_task.id),
+                status: TaskStatus::InProgress,
+            })
+            .unwrap();
+        let next = command_executor.execute_command_next().unwrap().unwrap();
+        assert_eq!(next.id, fish_task.id);
+    }
+
+    #[test]
+    fn test_execute_command_mark_done_recurring_task() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let due = NaiveDate::from_ymd_opt(2026, 1, 31).unwrap();
+        let task = command_executor
+            .execute_command_add(CommandAdd {
+                description: TaskDescription::new("pay rent".to_string()).unwrap(),
+                due: Some(due),
+                recur: Some(RecurrenceRule::parse("monthly", due).unwrap()),
+                milestone: None,
+                body: None,
+            })
+            .unwrap();
+        let result = command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap()
+            .remove(0);
+        let next_task = result.next_occurrence.unwrap();
+        assert_ne!(next_task.id, task.id);
+        assert_eq!(next_task.series, Some(task.id));
+        assert_eq!(next_task.status, TaskStatus::Todo);
+        assert_eq!(next_task.due, NaiveDate::from_ymd_opt(2026, 2, 28));
+        let result = command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(next_task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap()
+            .remove(0);
+        assert_eq!(
+            result.next_occurrence.unwrap().due,
+            NaiveDate::from_ymd_opt(2026, 3, 31)
+        );
+        let series = command_executor.execute_command_recur_list().unwrap();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].series, task.id);
+        assert_eq!(series[0].completed, 2);
+
+        assert!(matches!(
+            RecurrenceRule::parse("every:4000000000", due),
+            Err(Error::InvalidRecurrence { .. })
+        ));
+        assert!(RecurrenceRule::parse("every:3650", due).is_ok());
+        for rule in ["daily", "weekly", "weekdays", "every:3650", "monthly"] {
+            let rule = RecurrenceRule::parse(rule, due).unwrap();
+            assert!(matches!(
+                rule.next_due(NaiveDate::MAX),
+                Err(Error::NoNextOccurrence { .. })
+            ));
+        }
+        let task = command_executor
+            .execute_command_add(CommandAdd {
+                description: TaskDescription::new("pay forever".to_string()).unwrap(),
+                due: Some(NaiveDate::MAX),
+                recur: Some(RecurrenceRule::Daily),
+                milestone: None,
+                body: None,
+            })
+            .unwrap();
+        let result = command_executor.execute_command_set_status(CommandSetStatus {
+            selector: TaskSelector::Id(task.id),
+            status: TaskStatus::Done,
+        });
+        assert!(matches!(result, Err(Error::NoNextOccurrence { .. })));
+        let task = command_executor.db.get_task(&task.id).unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::Todo);
+    }
+
+    #[test]
+    fn test_execute_command_recur_stop() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let task = command_executor
+            .execute_command_add(CommandAdd {
+                description: TaskDescription::new("weekly report".to_string()).unwrap(),
+                due: None,
+                recur: Some(RecurrenceRule::Weekly),
+                milestone: None,
+                body: None,
+            })
+            .unwrap();
+        let result = command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap()
+            .remove(0);
+        let next_task = result.next_occurrence.unwrap();
+        let series = command_executor
+            .execute_command_recur_stop(CommandRecurStop { id: next_task.id })
+            .unwrap();
+        assert_eq!(series, task.id);
+        assert!(command_executor
+            .execute_command_recur_list()
+            .unwrap()
+            .is_empty());
+        let result = command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(next_task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap()
+            .remove(0);
+        assert!(result.next_occurrence.is_none());
+    }
+
+    #[test]
+    fn test_execute_command_add_after_delete_uses_fresh_id() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let fish_task = add_task(&mut command_executor, "feed the fish");
+        command_executor
+            .execute_command_delete(CommandDelete {
+                selector: TaskSelector::Id(dog_task.id),
+            })
+            .unwrap();
+        let cat_task = add_task(&mut command_executor, "pet the cat");
+        assert_ne!(cat_task.id, fish_task.id);
+    }
+
+    #[test]
+    fn test_execute_command_set_status_rejects_illegal_transition() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let task = add_task(&mut command_executor, "walk the dog");
+        command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap();
+        let result = command_executor.execute_command_set_status(CommandSetStatus {
+            selector: TaskSelector::Id(task.id),
+            status: TaskStatus::Done,
+        });
+        match result {
+            Err(Error::IllegalTransition { from, to, allowed }) => {
+                assert_eq!(from, "done");
+                assert_eq!(to, "done");
+                assert_eq!(allowed, "todo, in_progress");
+            }
+            _ => panic!("expected an illegal transition"),
+        }
+        let result = command_executor.execute_command_set_status(CommandSetStatus {
+            selector: TaskSelector::Id(task.id),
+            status: TaskStatus::InReview,
+        });
+        assert!(matches!(result, Err(Error::IllegalTransition { .. })));
+    }
+
+    #[test]
+    fn test_execute_command_set_status_custom_workflow() {
+        let db = Box::new(MockDb::new());
+        let config = Config {
+            workflow: WorkflowConfig {
+                statuses: vec![
+                    TaskStatus::Todo,
+                    TaskStatus::InProgress,
+                    TaskStatus::InReview,
+                    TaskStatus::Done,
+                ],
+                transitions: HashMap::from([
+                    (TaskStatus::Todo, vec![TaskStatus::InProgress]),
+                    (TaskStatus::InProgress, vec![TaskStatus::InReview]),
+                    (
+                        TaskStatus::InReview,
+                        vec![TaskStatus::InProgress, TaskStatus::Done],
+                    ),
+                ]),
+            },
+            ..Default::default()
+        };
+        let mut command_executor = CommandExecutor::new(db, config);
+        let task = add_task(&mut command_executor, "walk the dog");
+        let result = command_executor.execute_command_set_status(CommandSetStatus {
+            selector: TaskSelector::Id(task.id),
+            status: TaskStatus::Done,
+        });
+        assert!(matches!(result, Err(Error::IllegalTransition { .. })));
+        for status in [
+            TaskStatus::InProgress,
+            TaskStatus::InReview,
+            TaskStatus::Done,
+        ] {
+            let result = command_executor
+                .execute_command_set_status(CommandSetStatus {
+                    selector: TaskSelector::Id(task.id),
+                    status,
+                })
+                .unwrap()
+                .remove(0);
+            assert_eq!(result.new_status, status);
+        }
+    }
+
+    #[test]
+    fn test_execute_command_show_status_history() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let task = add_task(&mut command_executor, "walk the dog");
+        for status in [TaskStatus::InProgress, TaskStatus::Done] {
+            command_executor
+                .execute_command_set_status(CommandSetStatus {
+                    selector: TaskSelector::Id(task.id),
+                    status,
+                })
+                .unwrap();
+        }
+        let task = command_executor
+            .execute_command_show(CommandShow {
+                id: task.id,
+                json: false,
+            })
+            .unwrap()
+            .task;
+        assert_eq!(task.history.len(), 2);
+        assert_eq!(task.history[0].from, TaskStatus::Todo);
+        assert_eq!(task.history[0].to, TaskStatus::InProgress);
+        assert_eq!(task.history[1].to, TaskStatus::Done);
+        assert_eq!(task.completed_at(), Some(task.history[1].at));
+        assert!(task.lead_time().unwrap() >= task.cycle_time().unwrap());
+
+        command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(task.id),
+                status: TaskStatus::Todo,
+            })
+            .unwrap();
+        let task = command_executor
+            .execute_command_show(CommandShow {
+                id: task.id,
+                json: false,
+            })
+            .unwrap()
+            .task;
+        assert!(task.completed_at().is_none());
+        assert!(task.lead_time().is_none());
+    }
+
+    #[test]
+    fn test_execute_command_start_and_stop_timer() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let fish_task = add_task(&mut command_executor, "feed the fish");
+        let result = command_executor
+            .execute_command_start(CommandStart { id: dog_task.id })
+            .unwrap();
+        assert_eq!(result.task.status, TaskStatus::InProgress);
+        assert!(result.task.running_time_entry().is_some());
+        assert_eq!(result.status_change.unwrap().old_status, TaskStatus::Todo);
+        let result = command_executor.execute_command_start(CommandStart { id: fish_task.id });
+        assert!(matches!(result, Err(Error::TimerAlreadyRunning { .. })));
+        let result = command_executor.execute_command_stop().unwrap();
+        assert_eq!(result.task.id, dog_task.id);
+        assert!(result.task.running_time_entry().is_none());
+        assert!(matches!(
+            command_executor.execute_command_stop(),
+            Err(Error::NoRunningTimer)
+        ));
+        let result = command_executor
+            .execute_command_start(CommandStart { id: dog_task.id })
+            .unwrap();
+        assert!(result.status_change.is_none());
+        assert_eq!(result.task.time_entries.len(), 2);
+    }
+
+    #[test]
+    fn test_execute_command_log_time() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let task = add_task(&mut command_executor, "walk the dog");
+        let yesterday = Local::now().date_naive().pred_opt().unwrap();
+        let task = command_executor
+            .execute_command_log_time(CommandLogTime {
+                id: task.id,
+                duration: TimeDelta::minutes(90),
+                on: Some(yesterday),
+            })
+            .unwrap();
+        let task = command_executor
+            .execute_command_log_time(CommandLogTime {
+                id: task.id,
+                duration: TimeDelta::minutes(15),
+                on: None,
+            })
+            .unwrap();
+        assert_eq!(task.time_entries[0].start.date_naive(), yesterday);
+        assert_eq!(task.tracked_time(), TimeDelta::minutes(105));
+        assert_eq!(task.status, TaskStatus::Todo);
+        for duration in ["99999999999999999h", "9999999999999999m", "10000h"] {
+            let args = ["rtask", "log-time", "1", duration]
+                .map(String::from)
+                .to_vec();
+            assert!(matches!(
+                parse_command(args),
+                Err(Error::InvalidDuration { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_execute_command_timesheet() {
+        let db = Box::new(MockDb::new());
+        let config = Config {
+            timesheet_rounding: RoundingConfig {
+                minutes: 15,
+                mode: RoundingMode::Nearest,
+            },
+            ..Default::default()
+        };
+        let mut command_executor = CommandExecutor::new(db, config);
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let fish_task = add_task(&mut command_executor, "feed the fish");
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+        let tuesday = NaiveDate::from_ymd_opt(2026, 10, 13).unwrap();
+        let next_monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        for (id, minutes, on) in [
+            (dog_task.id, 50, monday),
+            (fish_task.id, 20, monday),
+            (dog_task.id, 5, tuesday),
+            (fish_task.id, 60, next_monday),
+        ] {
+            command_executor
+                .execute_command_log_time(CommandLogTime {
+                    id,
+                    duration: TimeDelta::minutes(minutes),
+                    on: Some(on),
+                })
+                .unwrap();
+        }
+        let timesheet = command_executor
+            .execute_command_timesheet(CommandTimesheet {
+                from: monday,
+                to: tuesday,
+                grouping: TimesheetGrouping::Day,
+                csv: false,
+            })
+            .unwrap();
+        assert_eq!(timesheet.rows.len(), 2);
+        assert_eq!(timesheet.rows[0].labels, vec!["2026-10-12".to_string()]);
+        assert_eq!(timesheet.rows[0].duration, TimeDelta::minutes(75));
+        assert_eq!(timesheet.rows[1].duration, TimeDelta::minutes(0));
+        assert_eq!(timesheet.total, TimeDelta::minutes(75));
+        let timesheet = command_executor
+            .execute_command_timesheet(CommandTimesheet {
+                from: monday,
+                to: next_monday,
+                grouping: TimesheetGrouping::Task,
+                csv: false,
+            })
+            .unwrap();
+        assert_eq!(timesheet.rows.len(), 2);
+        assert_eq!(timesheet.rows[0].labels[0], dog_task.id.to_string());
+        assert_eq!(timesheet.rows[0].duration, TimeDelta::minutes(60));
+        assert_eq!(timesheet.rows[1].duration, TimeDelta::minutes(75));
+
+        let args = ["rtask", "timesheet", "--week", "--from", "2026-01-01"]
+            .map(String::from)
+            .to_vec();
+        assert!(matches!(
+            parse_command(args),
+            Err(Error::ConflictingOptions { .. })
+        ));
+        let args = ["rtask", "timesheet", "--week"].map(String::from).to_vec();
+        let Ok(Command::Timesheet(command)) = parse_command(args) else {
+            panic!("expected a timesheet command");
+        };
+        assert_eq!(
+            (command.from, command.to),
+            week_bounds(Local::now().date_naive())
+        );
+    }
+
+    #[test]
+    fn test_execute_command_estimate_and_report() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let fish_task = add_task(&mut command_executor, "feed the fish");
+        let task = command_executor
+            .execute_command_estimate(CommandEstimate {
+                id: dog_task.id,
+                estimate: Estimate::from_str("2h").unwrap(),
+            })
+            .unwrap();
+        assert_eq!(task.estimate, Some(Estimate::Time { minutes: 120 }));
+        command_executor
+            .execute_command_estimate(CommandEstimate {
+                id: fish_task.id,
+                estimate: Estimate::from_str("3pt").unwrap(),
+            })
+            .unwrap();
+        let task = command_executor
+            .execute_command_log_time(CommandLogTime {
+                id: dog_task.id,
+                duration: TimeDelta::minutes(150),
+                on: None,
+            })
+            .unwrap();
+        assert_eq!(
+            task.remaining_estimate(),
+            Some(Estimate::Time { minutes: 0 })
+        );
+        assert!(command_executor
+            .execute_command_report_estimates()
+            .unwrap()
+            .rows
+            .is_empty());
+        command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(dog_task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap();
+        let report = command_executor.execute_command_report_estimates().unwrap();
+        assert_eq!(report.rows.len(), 1);
+        assert_eq!(report.rows[0].task.id, dog_task.id);
+        assert_eq!(report.estimated_time, TimeDelta::minutes(120));
+        assert_eq!(report.actual_for_time, TimeDelta::minutes(150));
+        assert_eq!(report.estimated_points, 0);
+
+        assert!(matches!(
+            Estimate::from_str("4000000000pt"),
+            Err(Error::InvalidEstimate { .. })
+        ));
+        assert_eq!(
+            Estimate::from_str("1000pt").unwrap(),
+            Estimate::Points { points: 1000 }
+        );
+        let mut total = EstimateTotal::default();
+        total.add(&Estimate::Points { points: u32::MAX });
+        total.add(&Estimate::Points { points: 1 });
+        assert_eq!(total.points, Some(u32::MAX));
+    }
+
+    #[test]
+    fn test_execute_command_roadmap() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let target = Local::now().date_naive() + chrono::Days::new(30);
+        command_executor
+            .execute_command_milestone_add(CommandMilestoneAdd {
+                name: "v1.0".to_string(),
+                target: Some(target),
+            })
+            .unwrap();
+        let result = command_executor.execute_command_milestone_add(CommandMilestoneAdd {
+            name: "v1.0".to_string(),
+            target: None,
+        });
+        assert!(matches!(result, Err(Error::MilestoneAlreadyExists { .. })));
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        add_task(&mut command_executor, "feed the fish");
+        let result = command_executor.execute_command_milestone_assign(CommandMilestoneAssign {
+            id: dog_task.id,
+            name: "v2.0".to_string(),
+        });
+        assert!(matches!(result, Err(Error::MilestoneNotFound { .. })));
+        let task = command_executor
+            .execute_command_milestone_assign(CommandMilestoneAssign {
+                id: dog_task.id,
+                name: "v1.0".to_string(),
+            })
+            .unwrap();
+        assert_eq!(task.milestone, Some("v1.0".to_string()));
+        let roadmap = command_executor.execute_command_roadmap().unwrap();
+        assert_eq!(roadmap.len(), 1);
+        assert_eq!(roadmap[0].total, 1);
+        assert_eq!(roadmap[0].open, 1);
+        assert_eq!(roadmap[0].percent(), 0);
+        command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(dog_task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap();
+        let roadmap = command_executor.execute_command_roadmap().unwrap();
+        assert_eq!(roadmap[0].percent(), 100);
+        assert_eq!(roadmap[0].schedule, Schedule::Complete);
+    }
+
+    #[test]
+    fn test_execute_command_sprint_plan_and_close() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let today = Local::now().date_naive();
+        let sprint = command_executor
+            .execute_command_sprint_add(CommandSprintAdd {
+                name: "s1".to_string(),
+                start: today,
+                end: None,
+                capacity: Some(Estimate::Points { points: 5 }),
+            })
+            .unwrap();
+        assert_eq!(sprint.end, today + Days::new(13));
+        command_executor
+            .execute_command_sprint_add(CommandSprintAdd {
+                name: "s2".to_string(),
+                start: today + Days::new(14),
+                end: None,
+                capacity: None,
+            })
+            .unwrap();
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let fish_task = add_task(&mut command_executor, "feed the fish");
+        command_executor
+            .execute_command_sprint_plan(CommandSprintPlan {
+                name: "s2".to_string(),
+                ids: vec![fish_task.id],
+            })
+            .unwrap();
+        for (id, points) in [(dog_task.id, 3), (fish_task.id, 3)] {
+            command_executor
+                .execute_command_estimate(CommandEstimate {
+                    id,
+                    estimate: Estimate::Points { points },
+                })
+                .unwrap();
+        }
+        let plan = command_executor
+            .execute_command_sprint_plan(CommandSprintPlan {
+                name: "s1".to_string(),
+                ids: vec![dog_task.id, fish_task.id],
+            })
+            .unwrap();
+        assert_eq!(plan.load.points, Some(6));
+        assert!(plan.is_over_capacity());
+        let s2 = &command_executor.db.get_sprints().unwrap()[1];
+        assert!(s2.committed.is_empty());
+        let board = command_executor.execute_command_sprint_current().unwrap();
+        assert_eq!(board.sprint.name, "s1");
+        assert_eq!(board.columns[0].0, TaskStatus::Todo);
+        assert_eq!(board.columns[0].1.len(), 2);
+        command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(dog_task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap();
+        let report = command_executor
+            .execute_command_sprint_close(CommandSprintClose { name: None })
+            .unwrap();
+        assert_eq!(report.committed, 2);
+        assert_eq!(report.completed, 1);
+        assert_eq!(report.completed_estimate.points, Some(3));
+        assert_eq!(report.carried_over, vec![fish_task.id]);
+        assert_eq!(report.next_sprint, Some("s2".to_string()));
+        let fish_task = command_executor
+            .execute_command_show(CommandShow {
+                id: fish_task.id,
+                json: false,
+            })
+            .unwrap()
+            .task;
+        assert_eq!(fish_task.sprint, Some("s2".to_string()));
+        let result = command_executor.execute_command_sprint_close(CommandSprintClose {
+            name: Some("s1".to_string()),
+        });
+        assert!(matches!(result, Err(Error::SprintClosed { .. })));
+        let result = command_executor.execute_command_sprint_close(CommandSprintClose {
+            name: Some("s2".to_string()),
+        });
+        assert!(matches!(result, Err(Error::NoNextSprint { .. })));
+    }
+
+    #[test]
+    fn test_execute_command_charts() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        command_executor
+            .execute_command_milestone_add(CommandMilestoneAdd {
+                name: "v1.0".to_string(),
+                target: None,
+            })
+            .unwrap();
+        let result = command_executor.execute_command_chart_burndown(CommandChartBurndown {
+            milestone: "v2.0".to_string(),
+            export: None,
+        });
+        assert!(matches!(result, Err(Error::MilestoneNotFound { .. })));
+        for description in ["walk the dog", "feed the fish"] {
+            let task = add_task(&mut command_executor, description);
+            command_executor
+                .execute_command_milestone_assign(CommandMilestoneAssign {
+                    id: task.id,
+                    name: "v1.0".to_string(),
+                })
+                .unwrap();
+        }
+        command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(TaskId::new(1).unwrap()),
+                status: TaskStatus::Done,
+            })
+            .unwrap();
+        let burndown = command_executor
+            .execute_command_chart_burndown(CommandChartBurndown {
+                milestone: "v1.0".to_string(),
+                export: None,
+            })
+            .unwrap();
+        assert_eq!(burndown.points, vec![(Local::now().date_naive(), 1)]);
+        let throughput = command_executor
+            .execute_command_chart_throughput(CommandChartThroughput {
+                weeks: 4,
+                export: None,
+            })
+            .unwrap();
+        assert_eq!(throughput.points.len(), 4);
+        assert_eq!(throughput.points[3].1, 1);
+        assert_eq!(throughput.to_csv().lines().next(), Some("week,completed"));
+    }
+
+    #[test]
+    fn test_execute_command_forecast() {
+        let forecast_command = || CommandForecast {
+            milestone: None,
+            sprint: None,
+            filter: None,
+            weeks: 4,
+            seed: Some(42),
+        };
+        let mut db = MockDb::new();
+        for id in 1..=6 {
+            let description = TaskDescription::new(format!("task {}", id)).unwrap();
+            db.tasks
+                .push(Task::new(TaskId::new(id).unwrap(), description));
+        }
+        let command_executor = CommandExecutor::new(Box::new(db), Config::default());
+        let result = command_executor.execute_command_forecast(forecast_command());
+        assert!(matches!(result, Err(Error::NotEnoughHistory { weeks: 4 })));
+
+        let mut db = MockDb::new();
+        for id in 1..=6 {
+            let description = TaskDescription::new(format!("task {}", id)).unwrap();
+            let mut task = Task::new(TaskId::new(id).unwrap(), description);
+            if id <= 4 {
+                task.status = TaskStatus::Done;
+                task.history.push(StatusChange {
+                    from: TaskStatus::Todo,
+                    to: TaskStatus::Done,
+                    at: Local::now() - TimeDelta::weeks(id as i64),
+                });
+            }
+            db.tasks.push(task);
+        }
+        let command_executor = CommandExecutor::new(Box::new(db), Config::default());
+        let forecast = command_executor
+            .execute_command_forecast(forecast_command())
+            .unwrap();
+        assert_eq!(forecast.remaining, 2);
+        assert_eq!(forecast.samples, vec![1, 1, 1, 1]);
+        let today = Local::now().date_naive();
+        for percentile in &forecast.percentiles {
+            assert_eq!(percentile.weeks, 2);
+            assert_eq!(percentile.date, today + Days::new(14));
+        }
+        let forecast = command_executor
+            .execute_command_forecast(CommandForecast {
+                filter: Some(Filter::parse(r#"desc~"task 6""#, today).unwrap()),
+                ..forecast_command()
+            })
+            .unwrap();
+        assert_eq!(forecast.remaining, 1);
+        assert_eq!(forecast.scope, "all open tasks matching --where");
+    }
+
+    #[test]
+    fn test_execute_command_stats() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let fish_task = add_task(&mut command_executor, "feed the fish");
+        add_task(&mut command_executor, "water the plants");
+        command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(dog_task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap();
+        let stats = command_executor
+            .execute_command_stats(CommandStats {
+                weeks: 2,
+                json: false,
+            })
+            .unwrap();
+        assert_eq!(stats.total, 3);
+        let counts: Vec<(&str, usize)> = stats
+            .by_status
+            .iter()
+            .map(|count| (count.status.as_str(), count.count))
+            .collect();
+        assert_eq!(counts, vec![("todo", 2), ("in_progress", 0), ("done", 1)]);
+        assert_eq!(stats.weeks.len(), 2);
+        assert_eq!(stats.weeks[1].created, 3);
+        assert_eq!(stats.weeks[1].completed, 1);
+        assert_eq!(
+            stats.oldest_open_task.as_ref().unwrap().id,
+            fish_task.id.value()
+        );
+        assert!(stats.average_lead_time.is_some());
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["by_status"][2]["count"], 1);
+        assert!(json["average_open_age_days"].is_f64());
+
+        for command in ["stats", "forecast", "chart throughput"] {
+            let mut args: Vec<String> = ["rtask"].map(String::from).to_vec();
+            args.extend(command.split(' ').map(String::from));
+            args.extend(["--weeks", "200000000"].map(String::from));
+            assert!(matches!(
+                parse_command(args),
+                Err(Error::InvalidOptionValue { .. })
+            ));
+        }
+        let args = ["rtask", "stats", "--weeks", "520"]
+            .map(String::from)
+            .to_vec();
+        let Ok(Command::Stats(command)) = parse_command(args) else {
+            panic!("expected a stats command");
+        };
+        assert_eq!(
+            command_executor
+                .execute_command_stats(command)
+                .unwrap()
+                .weeks
+                .len(),
+            520
+        );
+    }
+
+    #[test]
+    fn test_execute_command_heatmap() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(dog_task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap();
+        command_executor
+            .execute_command_log_time(CommandLogTime {
+                id: dog_task.id,
+                duration: TimeDelta::minutes(90),
+                on: Some(Local::now().date_naive() - Days::new(1)),
+            })
+            .unwrap();
+        let today = Local::now().date_naive();
+        let heatmap = command_executor
+            .execute_command_heatmap(CommandHeatmap {
+                metric: HeatmapMetric::Completed,
+            })
+            .unwrap();
+        assert_eq!(heatmap.start.weekday(), chrono::Weekday::Mon);
+        assert_eq!(
+            heatmap.values.len() as i64,
+            (today - heatmap.start).num_days() + 1
+        );
+        assert_eq!(heatmap.value(today), Some(1));
+        assert_eq!(heatmap.level(1), 4);
+        assert_eq!(heatmap.total(), 1);
+        let heatmap = command_executor
+            .execute_command_heatmap(CommandHeatmap {
+                metric: HeatmapMetric::Hours,
+            })
+            .unwrap();
+        assert_eq!(heatmap.value(today - Days::new(1)), Some(90));
+        assert_eq!(heatmap.value(today), Some(0));
+    }
+
+    #[test]
+    fn test_execute_command_standup() {
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        assert_eq!(
+            previous_working_day(monday),
+            NaiveDate::from_ymd_opt(2026, 10, 16).unwrap()
+        );
+        assert_eq!(previous_working_day(monday + Days::new(1)), monday);
+        let mut workflow = WorkflowConfig::default();
+        workflow.statuses.push(TaskStatus::Blocked);
+        workflow.transitions.insert(
+            TaskStatus::Todo,
+            vec![
+                TaskStatus::InProgress,
+                TaskStatus::Done,
+                TaskStatus::Blocked,
+            ],
+        );
+        let config = Config {
+            workflow,
+            ..Config::default()
+        };
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, config);
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let fish_task = add_task(&mut command_executor, "feed the fish");
+        let plants_task = add_task(&mut command_executor, "water the plants");
+        let cat_task = add_task(&mut command_executor, "brush the cat");
+        for (id, status) in [
+            (dog_task.id, TaskStatus::Done),
+            (fish_task.id, TaskStatus::InProgress),
+            (plants_task.id, TaskStatus::Blocked),
+        ] {
+            command_executor
+                .execute_command_set_status(CommandSetStatus {
+                    selector: TaskSelector::Id(id),
+                    status,
+                })
+                .unwrap();
+        }
+        command_executor
+            .execute_command_depend(CommandDepend {
+                id: cat_task.id,
+                on: fish_task.id,
+            })
+            .unwrap();
+        let standup = command_executor
+            .execute_command_standup(CommandStandup {
+                since: None,
+                format: StandupFormat::Markdown,
+            })
+            .unwrap();
+        let ids = |tasks: &[&Task]| tasks.iter().map(|task| task.id).collect::<Vec<TaskId>>();
+        assert_eq!(ids(&standup.done), vec![dog_task.id]);
+        assert_eq!(ids(&standup.in_progress), vec![fish_task.id]);
+        assert_eq!(standup.blocked.len(), 2);
+        assert_eq!(standup.blocked[0].task.id, plants_task.id);
+        assert_eq!(standup.blocked[1].blocked_by, vec![fish_task.id]);
+    }
+
+    #[test]
+    fn test_execute_command_log() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        add_task(&mut command_executor, "feed the fish");
+        let done = command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(dog_task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap()
+            .remove(0);
+        assert_eq!(done.new_status, TaskStatus::Done);
+        let completed_at = command_executor
+            .execute_command_show(CommandShow {
+                id: dog_task.id,
+                json: false,
+            })
+            .unwrap()
+            .task
+            .completed_at();
+        command_executor
+            .execute_command_update(CommandUpdate {
+                selector: TaskSelector::Id(dog_task.id),
+                description: TaskDescription::new("walk the dog twice".to_string()).unwrap(),
+            })
+            .unwrap();
+        let task = command_executor
+            .execute_command_show(CommandShow {
+                id: dog_task.id,
+                json: false,
+            })
+            .unwrap()
+            .task;
+        assert_eq!(task.completed_at(), completed_at);
+        assert!(task.updated_at > completed_at.unwrap());
+        command_executor
+            .execute_command_annotate(CommandAnnotate {
+                id: dog_task.id,
+                text: "took the long way".to_string(),
+            })
+            .unwrap();
+        command_executor
+            .execute_command_log_time(CommandLogTime {
+                id: dog_task.id,
+                duration: TimeDelta::minutes(30),
+                on: None,
+            })
+            .unwrap();
+
+        let today = Local::now().date_naive();
+        let days = command_executor
+            .execute_command_log(CommandLog {
+                since: today,
+                changes: false,
+            })
+            .unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].date, today);
+        assert_eq!(days[0].completed.len(), 1);
+        assert_eq!(days[0].completed[0].id, dog_task.id);
+        assert!(days[0].changes.is_empty());
+        let days = command_executor
+            .execute_command_log(CommandLog {
+                since: today,
+                changes: true,
+            })
+            .unwrap();
+        // Two creations, the status change, the description, the annotation and the time entry
+        assert_eq!(days[0].changes.len(), 6);
+        assert!(days[0].changes.iter().any(|change| matches!(
+            change.kind,
+            ChangeKind::Field {
+                field: "description",
+                value: Some("walk the dog twice"),
+            }
+        )));
+        assert!(days[0].changes.iter().any(|change| matches!(
+            change.kind,
+            ChangeKind::TimeLogged { duration } if duration == TimeDelta::minutes(30)
+        )));
+        let days = command_executor
+            .execute_command_log(CommandLog {
+                since: today + Days::new(1),
+                changes: true,
+            })
+            .unwrap();
+        assert!(days.is_empty());
+    }
+
+    #[test]
+    fn test_execute_command_list_with_filter() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let fish_task = add_task(&mut command_executor, "feed the fish");
+        let login_task = add_task(&mut command_executor, "fix the Login page");
+        command_executor
+            .execute_command_depend(CommandDepend {
+                id: fish_task.id,
+                on: dog_task.id,
+            })
+            .unwrap();
+        let today = Local::now().date_naive();
+        let list = |command_executor: &CommandExecutor, query: &str| {
+            let filter = Filter::parse(query, today).unwrap();
+            command_executor
+                .execute_command_list(CommandList {
+                    status: None,
+                    dependency: None,
+                    filter: Some(filter),
+                    sort: None,
+                    reverse: false,
+                    offset: 0,
+                    limit: None,
+                })
+                .unwrap()
+                .iter()
+                .map(|task| task.id)
+                .collect::<Vec<TaskId>>()
+        };
+        assert_eq!(
+            list(&command_executor, r#"desc~"login""#),
+            vec![login_task.id]
+        );
+        assert_eq!(
+            list(&command_executor, "status:todo and not is:blocked"),
+            vec![dog_task.id, login_task.id]
+        );
+        assert_eq!(
+            list(
+                &command_executor,
+                "(id>=2 and is:blocked) or desc:'walk the dog'"
+            ),
+            vec![dog_task.id, fish_task.id]
+        );
+        assert_eq!(list(&command_executor, "due:none id<2"), vec![dog_task.id]);
+        assert!(list(&command_executor, "due<tomorrow").is_empty());
+
+        let result = Filter::parse("status:todo and tag:backend", today);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidFilter { column: 17, .. })
+        ));
+        let result = Filter::parse("status<todo", today);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidFilter { column: 7, .. })
+        ));
+        let result = Filter::parse("(status:todo", today);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidFilter { column: 1, .. })
+        ));
+        let result = Filter::parse("due<someday", today);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidFilter { column: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn test_execute_command_set_status_with_filter() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let fish_task = add_task(&mut command_executor, "feed the fish");
+        let plants_task = add_task(&mut command_executor, "water the plants");
+        let today = Local::now().date_naive();
+        let results = command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Filter(Filter::parse("desc~the and id<=2", today).unwrap()),
+                status: TaskStatus::InProgress,
+            })
+            .unwrap();
+        let ids: Vec<TaskId> = results.iter().map(|result| result.task_id).collect();
+        assert_eq!(ids, vec![dog_task.id, fish_task.id]);
+
+        let config = Config {
+            workflow: WorkflowConfig {
+                transitions: HashMap::from([
+                    (TaskStatus::Todo, vec![TaskStatus::InProgress]),
+                    (TaskStatus::InProgress, vec![TaskStatus::Done]),
+                ]),
+                ..WorkflowConfig::default()
+            },
+            ..Config::default()
+        };
+        command_executor.config = config;
+        let result = command_executor.execute_command_set_status(CommandSetStatus {
+            selector: TaskSelector::Filter(Filter::parse("is:open", today).unwrap()),
+            status: TaskStatus::Done,
+        });
+        assert!(matches!(result, Err(Error::IllegalTransition { .. })));
+        let task = command_executor
+            .execute_command_show(CommandShow {
+                id: dog_task.id,
+                json: false,
+            })
+            .unwrap()
+            .task;
+        assert_eq!(task.status, TaskStatus::InProgress);
+
+        let deleted = command_executor
+            .execute_command_delete(CommandDelete {
+                selector: TaskSelector::Filter(Filter::parse("status:in_progress", today).unwrap()),
+            })
+            .unwrap();
+        assert_eq!(deleted, vec![dog_task.id, fish_task.id]);
+        let tasks = command_executor
+            .execute_command_list(CommandList {
+                status: None,
+                dependency: None,
+                filter: None,
+                sort: None,
+                reverse: false,
+                offset: 0,
+                limit: None,
+            })
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, plants_task.id);
+    }
+
+    #[test]
+    fn test_execute_command_list_sorted_and_paged() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let fish_task = add_task(&mut command_executor, "feed the fish");
+        let plants_task = add_task(&mut command_executor, "water the plants");
+        let cat_task = add_task(&mut command_executor, "brush the cat");
+        for (id, estimate) in [(dog_task.id, 2), (fish_task.id, 1), (plants_task.id, 2)] {
+            command_executor
+                .execute_command_estimate(CommandEstimate {
+                    id,
+                    estimate: Estimate::Points { points: estimate },
+                })
+                .unwrap();
+        }
+        let list = |command_executor: &CommandExecutor,
+                    sort: Option<&str>,
+                    reverse: bool,
+                    offset: usize,
+                    limit: Option<usize>| {
+            command_executor
+                .execute_command_list(CommandList {
+                    status: None,
+                    dependency: None,
+                    filter: None,
+                    sort: sort.map(|sort| SortOrder::from_str(sort).unwrap()),
+                    reverse,
+                    offset,
+                    limit,
+                })
+                .unwrap()
+                .iter()
+                .map(|task| task.id)
+                .collect::<Vec<TaskId>>()
+        };
+        assert_eq!(
+            list(&command_executor, Some("estimate-,id-"), false, 0, None),
+            vec![plants_task.id, dog_task.id, fish_task.id, cat_task.id]
+        );
+        assert_eq!(
+            list(&command_executor, Some("desc"), false, 1, Some(2)),
+            vec![fish_task.id, dog_task.id]
+        );
+        assert_eq!(
+            list(&command_executor, None, true, 0, Some(1)),
+            vec![cat_task.id]
+        );
+        command_executor.config.default_sort = SortOrder::from_str("estimate+").unwrap();
+        assert_eq!(
+            list(&command_executor, None, false, 0, None),
+            vec![fish_task.id, dog_task.id, plants_task.id, cat_task.id]
+        );
+        assert!(matches!(
+            SortOrder::from_str("priority-,due+"),
+            Err(Error::InvalidSortKey { .. })
+        ));
+    }
+
+    #[test]
+    fn test_execute_command_search() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let groceries_task = add_task(&mut command_executor, "Buy groceries for the week");
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        add_task(&mut command_executor, "Call the bank");
+        let search = |command_executor: &CommandExecutor, query: &str| {
+            command_executor
+                .execute_command_search(CommandSearch {
+                    query: query.to_string(),
+                    limit: None,
+                })
+                .unwrap()
+                .iter()
+                .map(|result| {
+                    let text = searchable_text(result.task);
+                    let words: Vec<String> = result
+                        .highlights
+                        .iter()
+                        .map(|range| text[range.clone()].to_string())
+                        .collect();
+                    (result.task.id, words)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            search(&command_executor, "grocries"),
+            vec![(groceries_task.id, vec!["groceries".to_string()])]
+        );
+        assert_eq!(
+            search(&command_executor, "dog walk"),
+            vec![(dog_task.id, vec!["walk".to_string(), "dog".to_string()])]
+        );
+        assert!(search(&command_executor, "passport").is_empty());
+
+        let mut index = SearchIndex::new(command_executor.db.get_tasks().unwrap());
+        index.remove(&groceries_task.id);
+        assert!(index.search("groceries").is_empty());
+        assert_eq!(
+            index.search("the dog").first().map(|hit| hit.id),
+            Some(dog_task.id)
+        );
+    }
+
+    #[test]
+    fn test_execute_command_on_id_lists_and_ranges() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let tasks: Vec<Task> = [
+            "walk the dog",
+            "feed the fish",
+            "water the plants",
+            "call mom",
+        ]
+        .iter()
+        .map(|description| add_task(&mut command_executor, description))
+        .collect();
+        let args = |line: &str| -> Vec<String> {
+            ["rtask"]
+                .iter()
+                .chain(line.split(' ').collect::<Vec<&str>>().iter())
+                .map(|arg| arg.to_string())
+                .collect()
+        };
+
+        let Ok(Command::SetStatus(command)) = parse_command(args("mark-done 1,3-4")) else {
+            panic!("expected a set-status command");
+        };
+        let results = command_executor
+            .execute_command_set_status(command)
+            .unwrap();
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result.task_id)
+                .collect::<Vec<TaskId>>(),
+            vec![tasks[0].id, tasks[2].id, tasks[3].id]
+        );
+
+        let Ok(Command::Delete(command)) = parse_command(args("delete 2,4,7-8")) else {
+            panic!("expected a delete command");
+        };
+        match command_executor.execute_command_delete(command) {
+            Err(Error::TaskNotFound { id }) => assert_eq!(id, "7, 8"),
+            _ => panic!("expected missing tasks to be reported"),
+        }
+        assert_eq!(command_executor.db.get_tasks().unwrap().len(), 4);
+
+        assert!(matches!(
+            parse_command(args("delete 4-2")),
+            Err(Error::InvalidIdRange { .. })
+        ));
+        for list in [
+            "delete 1-4294967295",
+            "delete 1-100000",
+            "delete 1-6000,7000-13000",
+        ] {
+            assert!(matches!(
+                parse_command(args(list)),
+                Err(Error::IdListTooLong { max: 10_000, .. })
+            ));
+        }
+        let Ok(Command::Delete(command)) = parse_command(args("delete 1-10000,1-10000")) else {
+            panic!("expected a delete command");
+        };
+        assert!(matches!(
+            command_executor.execute_command_delete(command),
+            Err(Error::TaskNotFound { .. })
+        ));
+        assert_eq!(command_executor.db.get_tasks().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_transaction_rolls_back_on_error() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let fish_task = add_task(&mut command_executor, "feed the fish");
+
+        let result: Result<()> = command_executor.transaction(|db| {
+            db.delete_task(&dog_task.id)?;
+            Err(Error::TaskNotFound {
+                id: "3".to_string(),
+            })
+        });
+        assert!(result.is_err());
+        assert_eq!(command_executor.db.get_tasks().unwrap().len(), 2);
+
+        command_executor
+            .transaction(|db| {
+                db.delete_task(&dog_task.id)?;
+                db.delete_task(&fish_task.id)
+            })
+            .unwrap();
+        assert!(command_executor.db.get_tasks().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_task_query() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let fish_task = add_task(&mut command_executor, "feed the fish");
+        let cat_task = add_task(&mut command_executor, "pet the cat");
+        command_executor
+            .execute_command_set_status(CommandSetStatus {
+                selector: TaskSelector::Id(fish_task.id),
+                status: TaskStatus::Done,
+            })
+            .unwrap();
+        let ids = |query: TaskQuery| {
+            command_executor
+                .db
+                .query(&query)
+                .unwrap()
+                .iter()
+                .map(|task| task.id)
+                .collect::<Vec<TaskId>>()
+        };
+
+        assert_eq!(
+            ids(TaskQuery::ids(vec![cat_task.id, dog_task.id])),
+            vec![cat_task.id, dog_task.id]
+        );
+        assert_eq!(
+            ids(TaskQuery {
+                status: Some(TaskStatus::Todo),
+                sort: Some(SortOrder::from_str("id-").unwrap()),
+                limit: Some(1),
+                ..Default::default()
+            }),
+            vec![cat_task.id]
+        );
+        assert_eq!(
+            ids(TaskQuery {
+                ids: Some(vec![dog_task.id, fish_task.id]),
+                status: Some(TaskStatus::Done),
+                ..Default::default()
+            }),
+            vec![fish_task.id]
+        );
+    }
+
+    #[test]
+    fn test_execute_command_show_related_tasks() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let leash_task = add_task(&mut command_executor, "buy a leash");
+        command_executor
+            .execute_command_depend(CommandDepend {
+                id: dog_task.id,
+                on: leash_task.id,
+            })
+            .unwrap();
+        let details = |id: TaskId| {
+            command_executor
+                .execute_command_show(CommandShow { id, json: true })
+                .unwrap()
+        };
+        let dog_details = details(dog_task.id);
+        assert_eq!(dog_details.dependencies[0].id, leash_task.id);
+        assert!(dog_details.dependents.is_empty());
+        let leash_details = details(leash_task.id);
+        assert!(leash_details.dependencies.is_empty());
+        assert_eq!(leash_details.dependents[0].id, dog_task.id);
+
+        assert_eq!(format_relative(TimeDelta::days(-3)), "3 days ago");
+        assert_eq!(format_relative(TimeDelta::minutes(61)), "in 1 hour");
+        assert_eq!(format_relative(TimeDelta::seconds(-20)), "just now");
+    }
+
+    #[test]
+    fn test_execute_command_edit() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        let dog_task = add_task(&mut command_executor, "walk the dog");
+        let leash_task = add_task(&mut command_executor, "buy a leash");
+
+        let mut opened: Vec<String> = vec![];
+        let mut editor = |text: &str| {
+            opened.push(text.to_string());
+            let edited = match opened.len() {
+                1 => {
+                    text.replace("status: todo", "status: someday")
+                        .replace("walk the dog", "walk the dog twice")
+                        + "# Error: the heading of a body\n"
+                }
+                _ => text
+                    .replace("status: someday", "status: in_progress")
+                    .replace("estimate: ", "estimate: 2h")
+                    .replace("depends_on: ", "depends_on: 2"),
+            };
+            Ok(edited)
+        };
+        let result = command_executor
+            .execute_command_edit(CommandEdit { id: dog_task.id }, &mut editor)
+            .unwrap()
+            .unwrap();
+        assert_eq!(opened.len(), 2);
+        assert!(opened[1].starts_with("# Error: Unknown status: someday"));
+        assert_eq!(result.task.description.to_string(), "walk the dog twice");
+        assert_eq!(result.task.status, TaskStatus::InProgress);
+        assert_eq!(result.task.estimate, Some(Estimate::Time { minutes: 120 }));
+        assert_eq!(result.task.depends_on, vec![leash_task.id]);
+        assert_eq!(
+            result.task.body,
+            Some("# Error: the heading of a body".to_string())
+        );
+        assert_eq!(
+            result.status_change.unwrap().blocked_by,
+            vec![leash_task.id]
+        );
+
+        let unchanged = command_executor
+            .execute_command_edit(CommandEdit { id: dog_task.id }, &mut |text| {
+                Ok(text.to_string())
+            })
+            .unwrap();
+        assert!(unchanged.is_none());
+
+        command_executor
+            .execute_command_edit(CommandEdit { id: dog_task.id }, &mut |text| {
+                Ok(text.replace("recur: \n", "recur: weekly\n"))
+            })
+            .unwrap()
+            .unwrap();
+        let series = command_executor.execute_command_recur_list().unwrap();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].series, dog_task.id);
+        assert_eq!(
+            command_executor
+                .execute_command_recur_stop(CommandRecurStop { id: dog_task.id })
+                .unwrap(),
+            dog_task.id
+        );
+
+        // The dependency on the deleted task is kept without reopening the editor
+        command_executor
+            .execute_command_delete(CommandDelete {
+                selector: TaskSelector::Id(leash_task.id),
+            })
+            .unwrap();
+        let result = command_executor
+            .execute_command_edit(CommandEdit { id: dog_task.id }, &mut |text| {
+                assert!(!text.starts_with("# Error"), "{}", text);
+                Ok(text.replace("walk the dog twice", "walk the dog at noon"))
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.task.description.to_string(), "walk the dog at noon");
+        assert_eq!(result.task.depends_on, vec![leash_task.id]);
+        let result =
+            command_executor.execute_command_edit(CommandEdit { id: dog_task.id }, &mut |text| {
+                match text.starts_with("# Error") {
+                    true => Err(Error::EditorFailed {
+                        editor: "test".to_string(),
+                    }),
+                    false => Ok(text.replace("depends_on: 2", "depends_on: 2, 9")),
+                }
+            });
+        assert!(matches!(result, Err(Error::EditorFailed { .. })));
+    }
+
+    #[test]
+    fn test_execute_command_annotate_and_body() {
+        let db = Box::new(MockDb::new());
+        let mut command_executor = CommandExecutor::new(db, Config::default());
+        assert_eq!(
+            TaskDescription::new("  order\u{7} parts\n".to_string())
+                .unwrap()
+                .to_string(),
+            "order parts"
+        );
+        assert_eq!(
+            TaskDescription::new("Fix\r\nlogin\tpage".to_string())
+                .unwrap()
+                .to_string(),
+            "Fix login page"
+        );
+        assert!(matches!(
+            TaskDescription::new(" \t ".to_string()),
+            Err(Error::EmptyDescription)
+        ));
+        let task = command_executor
+            .execute_command_add(CommandAdd {
+                description: TaskDescription::new("order parts".to_string()).unwrap(),
+                due: None,
+                recur: None,
+                milestone: None,
+                body: clean_body("\n## Parts\n- bolts\r\n- nuts\n"),
+            })
+            .unwrap();
+        assert_eq!(task.body, Some("## Parts\n- bolts\n- nuts".to_string()));
+
+        let task = command_executor
+            .execute_command_annotate(CommandAnnotate {
+                id: task.id,
+                text: " called vendor, waiting ".to_string(),
+            })
+            .unwrap();
+        assert_eq!(task.annotations[0].text, "called vendor, waiting");
+        assert!(matches!(
+            command_executor.execute_command_annotate(CommandAnnotate {
+                id: task.id,
+                text: "  ".to_string(),
+            }),
+            Err(Error::EmptyAnnotation)
+        ));
+
+        for query in ["vendro", "bolts"] {
+            let results = command_executor
+                .execute_command_search(CommandSearch {
+                    query: query.to_string(),
+                    limit: None,
+                })
+                .unwrap();
+            assert_eq!(results[0].task.id, task.id);
+        }
+    }
+
+    #[test]
+    fn test_execute_command_check() {
+        let db = Box::new(MockDb::new());
+        let config = Config {
+            auto_complete_checklists: true,
+            ..Default::default()
+        };
+        let mut command_executor = CommandExecutor::new(db, config);
+        let task = add_task(&mut command_executor, "release 1.2");
+        for text in ["tag release", "publish notes"] {
+            command_executor
+                .execute_command_check_add(CommandCheckAdd {
+                    id: task.id,
+                    text: text.to_string(),
+                })
+                .unwrap();
+        }
+        assert!(matches!(
+            command_executor.execute_command_check_add(CommandCheckAdd {
+                id: task.id,
+                text: " ".to_string(),
+            }),
+            Err(Error::EmptyChecklistItem)
+        ));
+        assert!(matches!(
+            command_executor.execute_command_check_done(CommandCheckDone {
+                id: task.id,
+                number: 3,
+            }),
+            Err(Error::ChecklistItemNotFound { number: 3, .. })
+        ));
+
+        let result = command_executor
+            .execute_command_check_done(CommandCheckDone {
+                id: task.id,
+                number: 2,
+            })
+            .unwrap();
+        assert_eq!(result.task.checklist_progress(), Some((1, 2)));
+        assert!(result.task.to_string().contains("Checklist: [1/2]"));
+        assert!(result.status_change.is_none());
+
+        let result = command_executor
+            .execute_command_check_done(CommandCheckDone {
+                id: task.id,
+                number: 1,
+            })
+            .unwrap();
+        assert_eq!(result.task.checklist_progress(), Some((2, 2)));
+        assert_eq!(result.status_change.unwrap().new_status, TaskStatus::Done);
+        assert_eq!(
+            command_executor
+                .db
+                .get_task(&task.id)
+                .unwrap()
+                .unwrap()
+                .status,
+            TaskStatus::Done
+        );
+    }
+
+    /// An empty directory of its own for each test using a real `Db`
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rtask-test-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn new_task(id: u32, description: &str) -> Task {
+        Task::new(
+            TaskId::new(id).unwrap(),
+            TaskDescription::new(description.to_string()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_db_transactions() {
+        let dir = temp_dir("transactions");
+        let path = dir.join("db.json");
+        let mut db = Db::open(path.clone()).unwrap();
+        db.create_task(new_task(1, "walk the dog")).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+
+        db.begin();
+        db.create_task(new_task(2, "feed the fish")).unwrap();
+        let mut task = db
+            .get_task(&TaskId::new(1).unwrap())
+            .unwrap()
+            .unwrap()
+            .clone();
+        task.set_description(TaskDescription::new("walk the cat".to_string()).unwrap());
+        db.update_task(&task.id.clone(), task).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), written);
+        db.rollback();
+        assert_eq!(db.get_tasks().unwrap().len(), 1);
+        assert_eq!(
+            db.get_tasks().unwrap()[0].description.to_string(),
+            "walk the dog"
+        );
+        assert!(db.get_task(&TaskId::new(2).unwrap()).unwrap().is_none());
+        assert_eq!(db.search("fish").unwrap().len(), 0);
+
+        // Staged changes are only written, all at once, by the commit
+        db.begin();
+        db.create_task(new_task(2, "feed the fish")).unwrap();
+        db.create_task(new_task(3, "water the plants")).unwrap();
+        db.delete_task(&TaskId::new(1).unwrap()).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), written);
+        db.commit().unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+        let reopened = Db::open(path.clone()).unwrap();
+        let descriptions: Vec<String> = reopened
+            .get_tasks()
+            .unwrap()
+            .iter()
+            .map(|task| task.description.to_string())
+            .collect();
+        assert_eq!(descriptions, vec!["feed the fish", "water the plants"]);
+
+        // A commit that can't be written leaves the state from before the transaction
+        let mut db = Db::open(dir.join("missing").join("db.json")).unwrap();
+        db.begin();
+        db.create_task(new_task(1, "walk the dog")).unwrap();
+        assert!(matches!(db.commit(), Err(Error::FailedToPersistChanges)));
+        assert!(db.get_tasks().unwrap().is_empty());
+        assert!(db.get_task(&TaskId::new(1).unwrap()).unwrap().is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_db_delete_in_transaction() {
+        let dir = temp_dir("delete");
+        let path = dir.join("db.json");
+        let mut db = Db::open(path.clone()).unwrap();
+        for id in 1..=8 {
+            db.create_task(new_task(id, &format!("task {}", id)))
+                .unwrap();
+        }
+        let check = |db: &Db, remaining: &[u32]| {
+            for id in 1..=8 {
+                let task = db.get_task(&TaskId::new(id).unwrap()).unwrap();
+                match remaining.contains(&id) {
+                    true => assert_eq!(
+                        task.unwrap().description.to_string(),
+                        format!("task {}", id)
+                    ),
+                    false => assert!(task.is_none()),
+                }
+            }
+            let ids: Vec<TaskId> = remaining
+                .iter()
+                .map(|id| TaskId::new(*id).unwrap())
+                .collect();
+            let found: Vec<TaskId> = db
+                .query(&TaskQuery::ids(ids.clone()))
+                .unwrap()
+                .iter()
+                .map(|task| task.id)
+                .collect();
+            assert_eq!(found, ids);
+        };
+
+        db.begin();
+        let mut remaining: Vec<u32> = (1..=8).collect();
+        for id in [2, 8, 1, 5] {
+            db.delete_task(&TaskId::new(id).unwrap()).unwrap();
+            remaining.retain(|other| *other != id);
+            check(&db, &remaining);
+        }
+        db.commit().unwrap();
+        check(&db, &remaining);
+        let ids: Vec<u32> = db
+            .get_tasks()
+            .unwrap()
+            .iter()
+            .map(|task| task.id.value())
+            .collect();
+        assert_eq!(ids, vec![3, 4, 6, 7]);
+        let reopened = Db::open(path).unwrap();
+        check(&reopened, &remaining);
+
+        // Outside a transaction the order is restored right away
+        db.delete_task(&TaskId::new(3).unwrap()).unwrap();
+        let ids: Vec<u32> = db
+            .get_tasks()
+            .unwrap()
+            .iter()
+            .map(|task| task.id.value())
+            .collect();
+        assert_eq!(ids, vec![4, 6, 7]);
+        check(&db, &[4, 6, 7]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,427 @@
+use chrono::NaiveDate;
+
+use crate::{
+    datetime::parse_date,
+    dependency,
+    task::{Task, TaskStatus},
+    Error, Result,
+};
+
+const FIELDS: &str = "id, status, desc, due, created, updated, completed, milestone, sprint, is";
+const STATES: &str = "open, closed, blocked, ready, recurring";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+impl std::fmt::Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Op::Eq => write!(f, ":"),
+            Op::Ne => write!(f, "!="),
+            Op::Lt => write!(f, "<"),
+            Op::Le => write!(f, "<="),
+            Op::Gt => write!(f, ">"),
+            Op::Ge => write!(f, ">="),
+            Op::Contains => write!(f, "~"),
+        }
+    }
+}
+
+impl Op {
+    fn compare<T: PartialOrd>(&self, actual: T, expected: T) -> bool {
+        match self {
+            Op::Eq | Op::Contains => actual == expected,
+            Op::Ne => actual != expected,
+            Op::Lt => actual < expected,
+            Op::Le => actual <= expected,
+            Op::Gt => actual > expected,
+            Op::Ge => actual >= expected,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateField {
+    Due,
+    Created,
+    Updated,
+    Completed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskState {
+    Open,
+    Closed,
+    Blocked,
+    Ready,
+    Recurring,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Id(Op, u32),
+    Status(Op, TaskStatus),
+    Description(Op, String),
+    /// `None` stands for `none`, i.e. no date
+    Date(DateField, Op, Option<NaiveDate>),
+    Milestone(Op, Option<String>),
+    Sprint(Op, Option<String>),
+    Is(TaskState),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Condition(Condition),
+}
+
+impl Filter {
+    /// Parses queries such as `status:todo and (due<friday or desc~"login")`.
+    /// `and` can be left out between two conditions
+    pub fn parse(query: &str, today: NaiveDate) -> Result<Self> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser {
+            query,
+            tokens,
+            position: 0,
+            today,
+        };
+        let filter = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some(token) => Err(parser.error(token.column, "unexpected token".to_string())),
+        }
+    }
+
+    /// `tasks` is needed to tell whether dependencies are done
+    pub fn matches(&self, task: &Task, tasks: &[Task]) -> bool {
+        match self {
+            Filter::And(left, right) => left.matches(task, tasks) && right.matches(task, tasks),
+            Filter::Or(left, right) => left.matches(task, tasks) || right.matches(task, tasks),
+            Filter::Not(filter) => !filter.matches(task, tasks),
+            Filter::Condition(condition) => condition.matches(task, tasks),
+        }
+    }
+}
+
+impl Condition {
+    fn matches(&self, task: &Task, tasks: &[Task]) -> bool {
+        match self {
+            Condition::Id(op, id) => op.compare(task.id.value(), *id),
+            Condition::Status(op, status) => op.compare(task.status == *status, true),
+            Condition::Description(op, text) => {
+                let description = task.description.to_string().to_lowercase();
+                match op {
+                    Op::Contains => description.contains(&text.to_lowercase()),
+                    op => op.compare(description, text.to_lowercase()),
+                }
+            }
+            Condition::Date(field, op, date) => {
+                let actual = match field {
+                    DateField::Due => task.due,
+                    DateField::Created => Some(task.created_at.date_naive()),
+                    DateField::Updated => Some(task.updated_at.date_naive()),
+                    DateField::Completed => task.completed_at().map(|at| at.date_naive()),
+                };
+                match (actual, date) {
+                    (Some(actual), Some(date)) => op.compare(actual, *date),
+                    (actual, None) => op.compare(actual.is_none(), true),
+                    (None, Some(_)) => *op == Op::Ne,
+                }
+            }
+            Condition::Milestone(op, name) => op.compare(&task.milestone, name),
+            Condition::Sprint(op, name) => op.compare(&task.sprint, name),
+            Condition::Is(state) => match state {
+                TaskState::Open => !task.status.is_closed(),
+                TaskState::Closed => task.status.is_closed(),
+                TaskState::Blocked => dependency::is_blocked(task, tasks),
+                TaskState::Ready => dependency::is_ready(task, tasks),
+                TaskState::Recurring => task.recur.is_some(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Quoted(String),
+    Op(Op),
+    OpenParen,
+    CloseParen,
+    And,
+    Or,
+    Not,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// 1-based, in characters
+    column: usize,
+}
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !"():=!<>~\"'".contains(c)
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = query.chars().collect();
+    let error = |column: usize, message: &str| Error::InvalidFilter {
+        query: query.to_string(),
+        column,
+        message: message.to_string(),
+    };
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let column = i + 1;
+        let next = chars.get(i + 1).copied();
+        let (kind, length) = match chars[i] {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => (TokenKind::OpenParen, 1),
+            ')' => (TokenKind::CloseParen, 1),
+            ':' | '=' => (TokenKind::Op(Op::Eq), 1),
+            '~' => (TokenKind::Op(Op::Contains), 1),
+            '!' if next == Some('=') => (TokenKind::Op(Op::Ne), 2),
+            '!' => return Err(error(column, "expected '=' after '!'")),
+            '<' if next == Some('=') => (TokenKind::Op(Op::Le), 2),
+            '<' => (TokenKind::Op(Op::Lt), 1),
+            '>' if next == Some('=') => (TokenKind::Op(Op::Ge), 2),
+            '>' => (TokenKind::Op(Op::Gt), 1),
+            quote @ ('"' | '\'') => {
+                let Some(length) = chars[i + 1..].iter().position(|c| *c == quote) else {
+                    return Err(error(column, "unterminated string"));
+                };
+                let text: String = chars[i + 1..i + 1 + length].iter().collect();
+                (TokenKind::Quoted(text), length + 2)
+            }
+            _ => {
+                let length = chars[i..].iter().take_while(|c| is_word_char(**c)).count();
+                let word: String = chars[i..i + length].iter().collect();
+                let kind = match word.to_lowercase().as_str() {
+                    "and" => TokenKind::And,
+                    "or" => TokenKind::Or,
+                    "not" => TokenKind::Not,
+                    _ => TokenKind::Word(word),
+                };
+                (kind, length)
+            }
+        };
+        tokens.push(Token { kind, column });
+        i += length;
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    query: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+    today: NaiveDate,
+}
+
+impl Parser<'_> {
+    fn error(&self, column: usize, message: String) -> Error {
+        Error::InvalidFilter {
+            query: self.query.to_string(),
+            column,
+            message,
+        }
+    }
+
+    fn end_column(&self) -> usize {
+        self.query.chars().count() + 1
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Filter> {
+        let mut filter = self.parse_and()?;
+        while self.peek().is_some_and(|token| token.kind == TokenKind::Or) {
+            self.next();
+            filter = Filter::Or(Box::new(filter), Box::new(self.parse_and()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<Filter> {
+        let mut filter = self.parse_unary()?;
+        loop {
+            match self.peek().map(|token| &token.kind) {
+                Some(TokenKind::And) => {
+                    self.next();
+                }
+                Some(TokenKind::Word(_) | TokenKind::Not | TokenKind::OpenParen) => (),
+                _ => return Ok(filter),
+            }
+            filter = Filter::And(Box::new(filter), Box::new(self.parse_unary()?));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter> {
+        match self.next() {
+            Some(Token {
+                kind: TokenKind::Not,
+                ..
+            }) => Ok(Filter::Not(Box::new(self.parse_unary()?))),
+            Some(Token {
+                kind: TokenKind::OpenParen,
+                column,
+            }) => {
+                let filter = self.parse_or()?;
+                match self.next() {
+                    Some(Token {
+                        kind: TokenKind::CloseParen,
+                        ..
+                    }) => Ok(filter),
+                    Some(token) => Err(self.error(token.column, "expected ')'".to_string())),
+                    None => Err(self.error(column, "unclosed '('".to_string())),
+                }
+            }
+            Some(Token {
+                kind: TokenKind::Word(field),
+                column,
+            }) => self.parse_condition(field, column),
+            Some(token) => Err(self.error(token.column, "expected a condition".to_string())),
+            None => Err(self.error(self.end_column(), "expected a condition".to_string())),
+        }
+    }
+
+    fn parse_condition(&mut self, field: String, field_column: usize) -> Result<Filter> {
+        let (op, op_column) = match self.next() {
+            Some(Token {
+                kind: TokenKind::Op(op),
+                column,
+            }) => (op, column),
+            Some(token) => {
+                return Err(self.error(
+                    token.column,
+                    format!("expected an operator after '{}'", field),
+                ))
+            }
+            None => {
+                return Err(self.error(
+                    self.end_column(),
+                    format!("expected an operator after '{}'", field),
+                ))
+            }
+        };
+        let (value, value_column) = match self.next() {
+            Some(Token {
+                kind: TokenKind::Word(value) | TokenKind::Quoted(value),
+                column,
+            }) => (value, column),
+            Some(token) => return Err(self.error(token.column, "expected a value".to_string())),
+            None => return Err(self.error(self.end_column(), "expected a value".to_string())),
+        };
+        let check_op = |allowed: &[Op]| match allowed.contains(&op) {
+            true => Ok(()),
+            false => Err(self.error(
+                op_column,
+                format!("operator '{}' cannot be used with '{}'", op, field),
+            )),
+        };
+        let invalid_value = |expected: &str| {
+            self.error(
+                value_column,
+                format!(
+                    "invalid value '{}' for '{}', expected {}",
+                    value, field, expected
+                ),
+            )
+        };
+        let optional = |value: &str| match value.eq_ignore_ascii_case("none") {
+            true => None,
+            false => Some(value.to_string()),
+        };
+        const EQUALITY: [Op; 2] = [Op::Eq, Op::Ne];
+        const ORDERING: [Op; 6] = [Op::Eq, Op::Ne, Op::Lt, Op::Le, Op::Gt, Op::Ge];
+        let condition = match field.to_lowercase().as_str() {
+            "id" => {
+                check_op(&ORDERING)?;
+                let id = value.parse().map_err(|_| invalid_value("a number"))?;
+                Condition::Id(op, id)
+            }
+            "status" => {
+                check_op(&EQUALITY)?;
+                let status = TaskStatus::from_str(&value).map_err(|_| invalid_value("a status"))?;
+                Condition::Status(op, status)
+            }
+            "desc" | "description" => {
+                check_op(&[Op::Eq, Op::Ne, Op::Contains])?;
+                Condition::Description(op, value.clone())
+            }
+            date_field @ ("due" | "created" | "updated" | "completed") => {
+                let date_field = match date_field {
+                    "due" => DateField::Due,
+                    "created" => DateField::Created,
+                    "updated" => DateField::Updated,
+                    _ => DateField::Completed,
+                };
+                let date = match optional(&value) {
+                    None => {
+                        check_op(&EQUALITY)?;
+                        None
+                    }
+                    Some(date) => {
+                        check_op(&ORDERING)?;
+                        Some(
+                            parse_date(&date, self.today)
+                                .map_err(|_| invalid_value("a date or none"))?,
+                        )
+                    }
+                };
+                Condition::Date(date_field, op, date)
+            }
+            "milestone" => {
+                check_op(&EQUALITY)?;
+                Condition::Milestone(op, optional(&value))
+            }
+            "sprint" => {
+                check_op(&EQUALITY)?;
+                Condition::Sprint(op, optional(&value))
+            }
+            "is" => {
+                check_op(&[Op::Eq])?;
+                let state = match value.to_lowercase().as_str() {
+                    "open" => TaskState::Open,
+                    "closed" => TaskState::Closed,
+                    "blocked" => TaskState::Blocked,
+                    "ready" => TaskState::Ready,
+                    "recurring" => TaskState::Recurring,
+                    _ => return Err(invalid_value(&format!("one of {}", STATES))),
+                };
+                Condition::Is(state)
+            }
+            _ => {
+                return Err(self.error(
+                    field_column,
+                    format!("unknown field '{}', expected one of {}", field, FIELDS),
+                ))
+            }
+        };
+        Ok(Filter::Condition(condition))
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::{Days, NaiveDate};
+
+use crate::{chart::throughput, datetime::week_bounds, task::Task, Error, Result};
+
+pub const FORECAST_TRIALS: u32 = 10_000;
+pub const FORECAST_CONFIDENCE: [u32; 3] = [50, 85, 95];
+/// Trials still running after this many simulated weeks stop there
+const MAX_SIMULATED_WEEKS: u32 = 520;
+
+/// SplitMix64, good enough to draw samples and seedable for reproducible forecasts
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..bound`, `bound` must not be 0
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+pub struct ForecastPercentile {
+    pub confidence: u32,
+    pub weeks: u32,
+    pub date: NaiveDate,
+}
+
+pub struct Forecast {
+    pub scope: String,
+    pub remaining: usize,
+    pub samples: Vec<u32>,
+    pub percentiles: Vec<ForecastPercentile>,
+}
+
+/// Weekly completion counts of the `weeks` full weeks before the current one
+pub fn weekly_samples(tasks: &[Task], weeks: u32, today: NaiveDate) -> Vec<u32> {
+    let (this_monday, _) = week_bounds(today);
+    let last_sunday = this_monday - Days::new(1);
+    throughput(tasks, weeks, last_sunday)
+        .points
+        .iter()
+        .map(|(_, completed)| *completed)
+        .collect()
+}
+
+/// Simulates how many weeks the `remaining` tasks take by drawing one of the
+/// sampled weekly counts for every simulated week
+pub fn forecast(
+    scope: String,
+    remaining: usize,
+    samples: Vec<u32>,
+    today: NaiveDate,
+    rng: &mut Rng,
+) -> Result<Forecast> {
+    if samples.iter().all(|completed| *completed == 0) {
+        return Err(Error::NotEnoughHistory {
+            weeks: samples.len() as u32,
+        });
+    }
+    let mut outcomes: Vec<u32> = (0..FORECAST_TRIALS)
+        .map(|_| {
+            let mut left = remaining as u32;
+            let mut weeks = 0;
+            while left > 0 && weeks < MAX_SIMULATED_WEEKS {
+                left = left.saturating_sub(samples[rng.below(samples.len())]);
+                weeks += 1;
+            }
+            weeks
+        })
+        .collect();
+    outcomes.sort();
+    let percentiles = FORECAST_CONFIDENCE
+        .iter()
+        .map(|confidence| {
+            let index = (outcomes.len() * *confidence as usize).div_ceil(100) - 1;
+            let weeks = outcomes[index];
+            ForecastPercentile {
+                confidence: *confidence,
+                weeks,
+                date: today + Days::new(7 * weeks as u64),
+            }
+        })
+        .collect();
+    Ok(Forecast {
+        scope,
+        remaining,
+        samples,
+        percentiles,
+    })
+}
@@ -0,0 +1,80 @@
+use chrono::{Days, NaiveDate};
+
+use crate::{datetime::week_bounds, task::Task};
+
+pub const HEATMAP_WEEKS: u64 = 52;
+pub const HEATMAP_LEVELS: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeatmapMetric {
+    Completed,
+    /// Tracked time, counted in minutes
+    Hours,
+}
+
+/// One value per day from `start`, a monday, up to `today`
+pub struct Heatmap {
+    pub metric: HeatmapMetric,
+    pub start: NaiveDate,
+    pub today: NaiveDate,
+    pub values: Vec<u32>,
+}
+
+impl Heatmap {
+    pub fn value(&self, day: NaiveDate) -> Option<u32> {
+        let offset = (day - self.start).num_days();
+        usize::try_from(offset)
+            .ok()
+            .and_then(|offset| self.values.get(offset).copied())
+    }
+
+    pub fn max(&self) -> u32 {
+        self.values.iter().copied().max().unwrap_or(0)
+    }
+
+    pub fn total(&self) -> u32 {
+        self.values.iter().sum()
+    }
+
+    /// 0 for empty days, then 1 to `HEATMAP_LEVELS` relative to the busiest day
+    pub fn level(&self, value: u32) -> u32 {
+        match (value, self.max()) {
+            (0, _) | (_, 0) => 0,
+            (value, max) => (value * HEATMAP_LEVELS).div_ceil(max),
+        }
+    }
+}
+
+pub fn build_heatmap(tasks: &[Task], metric: HeatmapMetric, today: NaiveDate) -> Heatmap {
+    let (this_monday, _) = week_bounds(today);
+    let start = this_monday - Days::new(7 * (HEATMAP_WEEKS - 1));
+    let mut values = vec![0; (today - start).num_days() as usize + 1];
+    let mut add = |day: NaiveDate, value: u32| {
+        if let Ok(offset) = usize::try_from((day - start).num_days()) {
+            if let Some(total) = values.get_mut(offset) {
+                *total += value;
+            }
+        }
+    };
+    for task in tasks {
+        match metric {
+            HeatmapMetric::Completed => {
+                if let Some(completed_at) = task.completed_at() {
+                    add(completed_at.date_naive(), 1);
+                }
+            }
+            HeatmapMetric::Hours => task.time_entries.iter().for_each(|entry| {
+                add(
+                    entry.start.date_naive(),
+                    entry.duration().num_minutes() as u32,
+                )
+            }),
+        }
+    }
+    Heatmap {
+        metric,
+        start,
+        today,
+        values,
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::{DateTime, Local, NaiveDate, TimeDelta};
+
+use crate::task::{Task, TaskStatus};
+
+pub enum ChangeKind<'a> {
+    Created,
+    Status {
+        from: TaskStatus,
+        to: TaskStatus,
+    },
+    /// `None` when the field was cleared, or for the body
+    Field {
+        field: &'a str,
+        value: Option<&'a str>,
+    },
+    TimerStarted,
+    TimeLogged {
+        duration: TimeDelta,
+    },
+    Annotated {
+        text: &'a str,
+    },
+}
+
+pub struct JournalChange<'a> {
+    pub at: DateTime<Local>,
+    pub task: &'a Task,
+    pub kind: ChangeKind<'a>,
+}
+
+pub struct JournalDay<'a> {
+    pub date: NaiveDate,
+    pub completed: Vec<&'a Task>,
+    pub changes: Vec<JournalChange<'a>>,
+}
+
+/// Days since `since` with at least one completed task, or with any change
+/// when `with_changes` is set, oldest first. Changes are creations, status and
+/// field changes, time entries, at the time they ended, and annotations
+pub fn build_journal(tasks: &[Task], since: NaiveDate, with_changes: bool) -> Vec<JournalDay<'_>> {
+    let mut completed: Vec<(DateTime<Local>, &Task)> = tasks
+        .iter()
+        .filter_map(|task| task.completed_at().map(|completed_at| (completed_at, task)))
+        .filter(|(completed_at, _)| completed_at.date_naive() >= since)
+        .collect();
+    completed.sort_by_key(|(completed_at, _)| *completed_at);
+
+    let mut changes: Vec<JournalChange> = vec![];
+    if with_changes {
+        for task in tasks {
+            changes.push(JournalChange {
+                at: task.created_at,
+                task,
+                kind: ChangeKind::Created,
+            });
+            task.history.iter().for_each(|change| {
+                changes.push(JournalChange {
+                    at: change.at,
+                    task,
+                    kind: ChangeKind::Status {
+                        from: change.from,
+                        to: change.to,
+                    },
+                })
+            });
+            task.changes.iter().for_each(|change| {
+                changes.push(JournalChange {
+                    at: change.at,
+                    task,
+                    kind: ChangeKind::Field {
+                        field: &change.field,
+                        value: change.value.as_deref(),
+                    },
+                })
+            });
+            task.time_entries.iter().for_each(|entry| {
+                changes.push(match entry.end {
+                    Some(end) => JournalChange {
+                        at: end,
+                        task,
+                        kind: ChangeKind::TimeLogged {
+                            duration: entry.duration(),
+                        },
+                    },
+                    None => JournalChange {
+                        at: entry.start,
+                        task,
+                        kind: ChangeKind::TimerStarted,
+                    },
+                })
+            });
+            task.annotations.iter().for_each(|annotation| {
+                changes.push(JournalChange {
+                    at: annotation.at,
+                    task,
+                    kind: ChangeKind::Annotated {
+                        text: &annotation.text,
+                    },
+                })
+            });
+        }
+        changes.retain(|change| change.at.date_naive() >= since);
+        changes.sort_by_key(|change| change.at);
+    }
+
+    let mut days: Vec<JournalDay> = vec![];
+    for (completed_at, task) in completed {
+        day_mut(&mut days, completed_at.date_naive())
+            .completed
+            .push(task);
+    }
+    for change in changes {
+        day_mut(&mut days, change.at.date_naive())
+            .changes
+            .push(change);
+    }
+    days.sort_by_key(|day| day.date);
+    days
+}
+
+fn day_mut<'b, 'a>(days: &'b mut Vec<JournalDay<'a>>, date: NaiveDate) -> &'b mut JournalDay<'a> {
+    let position = match days.iter().position(|day| day.date == date) {
+        Some(position) => position,
+        None => {
+            days.push(JournalDay {
+                date,
+                completed: vec![],
+                changes: vec![],
+            });
+            days.len() - 1
+        }
+    };
+    &mut days[position]
+}
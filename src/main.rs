@@ -10,15 +10,40 @@ use command::{parse_command, Command};
 pub use error::{Error, Result};
 use execute_command::CommandExecutor;
 use presentation::{
-    show_added_task, show_deleted_task, show_help, show_tasks, show_updated_status,
-    show_updated_task,
+    show_added_checklist_item, show_added_milestone, show_added_sprint, show_added_task,
+    show_annotated_task, show_chart, show_checked_item, show_deleted_tasks, show_edited_task,
+    show_estimate_report, show_forecast, show_heatmap, show_help, show_journal, show_logged_time,
+    show_next_task, show_recurring_series, show_roadmap, show_search_results, show_sprint_board,
+    show_sprint_close_report, show_sprint_plan, show_standup, show_started_timer, show_stats,
+    show_stats_json, show_stopped_series, show_stopped_timer, show_task_details,
+    show_task_details_json, show_tasks, show_timesheet, show_timesheet_csv, show_updated_statuses,
+    show_updated_task, show_updated_tasks,
 };
 
+mod chart;
 mod command;
+mod config;
+mod datetime;
 mod db;
+mod dependency;
+mod edit;
 mod execute_command;
+mod filter;
+mod forecast;
+mod heatmap;
+mod journal;
+mod milestone;
 mod presentation;
+mod query;
+mod recurrence;
+mod report;
+mod search;
+mod sort;
+mod sprint;
+mod standup;
+mod stats;
 mod task;
+mod timesheet;
 
 fn main() {
     match run() {
@@ -40,7 +65,8 @@ fn run() -> Result<()> {
     let command = parse_command(args)?;
 
     let db = Box::new(db::Db::new()?);
-    let mut command_executor = CommandExecutor::new(db);
+    let config = config::Config::load()?;
+    let mut command_executor = CommandExecutor::new(db, config);
 
     let result = match command {
         Command::Add(command_add) => command_executor
@@ -49,24 +75,161 @@ fn run() -> Result<()> {
             .map(|_| ()),
         Command::Update(command_update) => command_executor
             .execute_command_update(command_update)
-            .inspect(show_updated_task)
+            .inspect(|tasks| show_updated_tasks(tasks))
             .map(|_| ()),
         Command::Delete(command_delete) => command_executor
             .execute_command_delete(command_delete)
-            .inspect(show_deleted_task)
-            .map(|_| ()),
-        Command::MarkInProgress(command_mark_in_progress) => command_executor
-            .execute_command_mark_in_progress(command_mark_in_progress)
-            .inspect(show_updated_status)
+            .inspect(|ids| show_deleted_tasks(ids))
             .map(|_| ()),
-        Command::MarkDone(command_mark_done) => command_executor
-            .execute_command_mark_done(command_mark_done)
-            .inspect(show_updated_status)
+        Command::SetStatus(command_set_status) => command_executor
+            .execute_command_set_status(command_set_status)
+            .inspect(|results| show_updated_statuses(results))
             .map(|_| ()),
         Command::List(command_list) => command_executor
             .execute_command_list(command_list)
             .inspect(show_tasks)
             .map(|_| ()),
+        Command::Depend(command_depend) => command_executor
+            .execute_command_depend(command_depend)
+            .inspect(show_updated_task)
+            .map(|_| ()),
+        Command::Next => command_executor
+            .execute_command_next()
+            .inspect(show_next_task)
+            .map(|_| ()),
+        Command::Show(command_show) => {
+            let json = command_show.json;
+            command_executor
+                .execute_command_show(command_show)
+                .inspect(|details| match json {
+                    true => show_task_details_json(details),
+                    false => show_task_details(details),
+                })
+                .map(|_| ())
+        }
+        Command::Edit(command_edit) => command_executor
+            .execute_command_edit(command_edit, &mut edit::open_in_editor)
+            .inspect(show_edited_task)
+            .map(|_| ()),
+        Command::Annotate(command_annotate) => command_executor
+            .execute_command_annotate(command_annotate)
+            .inspect(show_annotated_task)
+            .map(|_| ()),
+        Command::CheckAdd(command_check_add) => command_executor
+            .execute_command_check_add(command_check_add)
+            .inspect(show_added_checklist_item)
+            .map(|_| ()),
+        Command::CheckDone(command_check_done) => command_executor
+            .execute_command_check_done(command_check_done)
+            .inspect(show_checked_item)
+            .map(|_| ()),
+        Command::Start(command_start) => command_executor
+            .execute_command_start(command_start)
+            .inspect(show_started_timer)
+            .map(|_| ()),
+        Command::Stop => command_executor
+            .execute_command_stop()
+            .inspect(show_stopped_timer)
+            .map(|_| ()),
+        Command::LogTime(command_log_time) => command_executor
+            .execute_command_log_time(command_log_time)
+            .inspect(show_logged_time)
+            .map(|_| ()),
+        Command::Timesheet(command_timesheet) => {
+            let csv = command_timesheet.csv;
+            command_executor
+                .execute_command_timesheet(command_timesheet)
+                .inspect(|timesheet| match csv {
+                    true => show_timesheet_csv(timesheet),
+                    false => show_timesheet(timesheet),
+                })
+                .map(|_| ())
+        }
+        Command::Estimate(command_estimate) => command_executor
+            .execute_command_estimate(command_estimate)
+            .inspect(show_updated_task)
+            .map(|_| ()),
+        Command::ReportEstimates => command_executor
+            .execute_command_report_estimates()
+            .inspect(show_estimate_report)
+            .map(|_| ()),
+        Command::MilestoneAdd(command_milestone_add) => command_executor
+            .execute_command_milestone_add(command_milestone_add)
+            .inspect(show_added_milestone)
+            .map(|_| ()),
+        Command::MilestoneAssign(command_milestone_assign) => command_executor
+            .execute_command_milestone_assign(command_milestone_assign)
+            .inspect(show_updated_task)
+            .map(|_| ()),
+        Command::Roadmap => command_executor
+            .execute_command_roadmap()
+            .inspect(show_roadmap)
+            .map(|_| ()),
+        Command::SprintAdd(command_sprint_add) => command_executor
+            .execute_command_sprint_add(command_sprint_add)
+            .inspect(show_added_sprint)
+            .map(|_| ()),
+        Command::SprintPlan(command_sprint_plan) => command_executor
+            .execute_command_sprint_plan(command_sprint_plan)
+            .inspect(show_sprint_plan)
+            .map(|_| ()),
+        Command::SprintCurrent => command_executor
+            .execute_command_sprint_current()
+            .inspect(show_sprint_board)
+            .map(|_| ()),
+        Command::SprintClose(command_sprint_close) => command_executor
+            .execute_command_sprint_close(command_sprint_close)
+            .inspect(show_sprint_close_report)
+            .map(|_| ()),
+        Command::ChartBurndown(command_chart_burndown) => command_executor
+            .execute_command_chart_burndown(command_chart_burndown)
+            .inspect(show_chart)
+            .map(|_| ()),
+        Command::ChartThroughput(command_chart_throughput) => command_executor
+            .execute_command_chart_throughput(command_chart_throughput)
+            .inspect(show_chart)
+            .map(|_| ()),
+        Command::Forecast(command_forecast) => command_executor
+            .execute_command_forecast(command_forecast)
+            .inspect(show_forecast)
+            .map(|_| ()),
+        Command::Stats(command_stats) => {
+            let json = command_stats.json;
+            command_executor
+                .execute_command_stats(command_stats)
+                .inspect(|stats| match json {
+                    true => show_stats_json(stats),
+                    false => show_stats(stats),
+                })
+                .map(|_| ())
+        }
+        Command::Heatmap(command_heatmap) => command_executor
+            .execute_command_heatmap(command_heatmap)
+            .inspect(show_heatmap)
+            .map(|_| ()),
+        Command::Standup(command_standup) => {
+            let format = command_standup.format;
+            command_executor
+                .execute_command_standup(command_standup)
+                .inspect(|standup| show_standup(standup, format))
+                .map(|_| ())
+        }
+        Command::Log(command_log) => command_executor
+            .execute_command_log(command_log)
+            .inspect(show_journal)
+            .map(|_| ()),
+        Command::Search(command_search) => command_executor
+            .execute_command_search(command_search)
+            .inspect(|results| show_search_results(results))
+            .map(|_| ()),
+        Command::RecurList => command_executor
+            .execute_command_recur_list()
+            .inspect(show_recurring_series)
+            .map(|_| ()),
+        Command::RecurStop(command_recur_stop) => command_executor
+            .execute_command_recur_stop(command_recur_stop)
+            .inspect(show_stopped_series)
+            .map(|_| ()),
     };
     result?;
 
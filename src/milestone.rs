@@ -0,0 +1,107 @@
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::task::{Task, TaskStatus};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Milestone {
+    pub name: String,
+    pub target: Option<NaiveDate>,
+    pub created_at: DateTime<Local>,
+}
+
+impl Milestone {
+    pub fn new(name: String, target: Option<NaiveDate>) -> Self {
+        Milestone {
+            name,
+            target,
+            created_at: Local::now(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Schedule {
+    Complete,
+    OnTrack,
+    Behind,
+    Overdue,
+    NoTarget,
+}
+
+impl std::fmt::Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Schedule::Complete => write!(f, "complete"),
+            Schedule::OnTrack => write!(f, "on track"),
+            Schedule::Behind => write!(f, "behind"),
+            Schedule::Overdue => write!(f, "overdue"),
+            Schedule::NoTarget => write!(f, "no target"),
+        }
+    }
+}
+
+pub struct MilestoneProgress<'a> {
+    pub milestone: &'a Milestone,
+    pub total: usize,
+    pub done: usize,
+    pub open: usize,
+    pub schedule: Schedule,
+}
+
+impl MilestoneProgress<'_> {
+    pub fn percent(&self) -> u32 {
+        match self.total {
+            0 => 0,
+            total => (self.done * 100 / total) as u32,
+        }
+    }
+}
+
+/// Cancelled tasks are left out. A milestone is on track when its share of done
+/// tasks is at least the share of time elapsed between its creation and its target
+pub fn milestone_progress<'a>(
+    milestone: &'a Milestone,
+    tasks: &[Task],
+    today: NaiveDate,
+) -> MilestoneProgress<'a> {
+    let milestone_tasks: Vec<&Task> = tasks
+        .iter()
+        .filter(|task| task.milestone.as_ref() == Some(&milestone.name))
+        .filter(|task| task.status != TaskStatus::Cancelled)
+        .collect();
+    let total = milestone_tasks.len();
+    let done = milestone_tasks
+        .iter()
+        .filter(|task| task.status == TaskStatus::Done)
+        .count();
+    let open = total - done;
+    let schedule = match milestone.target {
+        _ if total > 0 && open == 0 => Schedule::Complete,
+        None => Schedule::NoTarget,
+        Some(target) if today > target => Schedule::Overdue,
+        Some(target) => {
+            let start = milestone.created_at.date_naive();
+            let span = (target - start).num_days();
+            let expected = match span {
+                span if span <= 0 => 1.0,
+                span => (today - start).num_days() as f64 / span as f64,
+            };
+            let actual = match total {
+                0 => 1.0,
+                total => done as f64 / total as f64,
+            };
+            match actual >= expected {
+                true => Schedule::OnTrack,
+                false => Schedule::Behind,
+            }
+        }
+    };
+    MilestoneProgress {
+        milestone,
+        total,
+        done,
+        open,
+        schedule,
+    }
+}
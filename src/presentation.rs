@@ -1,10 +1,233 @@
+use chrono::{DateTime, Datelike, Days, Local, TimeDelta};
+
 use crate::{
-    execute_command::UpdateStatusResult,
-    task::{Task, TaskId},
+    chart::{ChartKind, ChartSeries},
+    datetime::{format_duration, format_relative, format_relative_date},
+    execute_command::{
+        CheckResult, EditResult, RecurringSeries, StartTimerResult, StopTimerResult, TaskDetails,
+        UpdateStatusResult,
+    },
+    forecast::{Forecast, FORECAST_TRIALS},
+    heatmap::{Heatmap, HeatmapMetric, HEATMAP_WEEKS},
+    journal::{ChangeKind, JournalDay},
+    milestone::{Milestone, MilestoneProgress},
+    report::EstimateReport,
+    search::{searchable_text, SearchResult},
+    sprint::{Sprint, SprintBoard, SprintCloseReport, SprintPlan},
+    standup::{Standup, StandupFormat},
+    stats::Stats,
+    task::{Estimate, EstimateTotal, Task, TaskId, TaskStatus},
+    timesheet::Timesheet,
 };
 
 pub fn show_tasks(tasks: &Vec<&Task>) {
     tasks.iter().for_each(|task| println!("{}", task));
+    show_remaining_estimates(tasks);
+}
+
+fn show_remaining_estimates(tasks: &[&Task]) {
+    let mut totals: Vec<(TaskStatus, EstimateTotal)> = vec![];
+    for task in tasks {
+        let Some(remaining) = task.remaining_estimate() else {
+            continue;
+        };
+        match totals.iter_mut().find(|(status, _)| *status == task.status) {
+            Some((_, total)) => total.add(&remaining),
+            None => {
+                let mut total = EstimateTotal::default();
+                total.add(&remaining);
+                totals.push((task.status, total));
+            }
+        }
+    }
+    if totals.is_empty() {
+        return;
+    }
+    println!("Remaining estimate:");
+    totals
+        .iter()
+        .for_each(|(status, total)| println!("  {}: {}", status, total));
+}
+
+pub fn show_task_details(details: &TaskDetails) {
+    let task = &details.task;
+    let now = Local::now();
+    let at = |at: DateTime<Local>| {
+        format!(
+            "{} ({})",
+            at.format("%Y-%m-%d %H:%M"),
+            format_relative(at - now)
+        )
+    };
+    let field = |name: &str, value: String| println!("  {:<12}{}", format!("{}:", name), value);
+
+    println!("Task {}: {}", task.id, task.description);
+    field("Status", task.status.to_string());
+    field("Created", at(task.created_at));
+    field("Updated", at(task.updated_at));
+    if let Some(completed_at) = task.completed_at() {
+        field("Completed", at(completed_at));
+    }
+    if let Some(due) = task.due {
+        let overdue = match !task.status.is_closed() && due < now.date_naive() {
+            true => ", overdue",
+            false => "",
+        };
+        field(
+            "Due",
+            format!(
+                "{} ({}{})",
+                due,
+                format_relative_date(due, now.date_naive()),
+                overdue
+            ),
+        );
+    }
+    if let Some(recur) = task.recur {
+        field("Repeats", recur.to_string());
+    }
+    if let Some(milestone) = &task.milestone {
+        field("Milestone", milestone.clone());
+    }
+    if let Some(sprint) = &task.sprint {
+        field("Sprint", sprint.clone());
+    }
+    if let Some(estimate) = task.estimate {
+        match task.remaining_estimate() {
+            Some(remaining) if remaining != estimate => field(
+                "Estimate",
+                format!("{} ({} remaining)", estimate, remaining),
+            ),
+            _ => field("Estimate", estimate.to_string()),
+        }
+    }
+    if !task.time_entries.is_empty() {
+        let running = match task.running_time_entry() {
+            Some(_) => " (timer running)",
+            None => "",
+        };
+        field(
+            "Tracked",
+            format!("{}{}", format_duration(task.tracked_time()), running),
+        );
+    }
+    if let Some(lead_time) = task.lead_time() {
+        field("Lead Time", format_duration(lead_time));
+    }
+    if let Some(cycle_time) = task.cycle_time() {
+        field("Cycle Time", format_duration(cycle_time));
+    }
+
+    if let Some(body) = &task.body {
+        println!();
+        body.lines().for_each(|line| println!("  {}", line));
+    }
+
+    let related = |title: &str, tasks: &[Task]| {
+        if !tasks.is_empty() {
+            println!("\n{}:", title);
+            tasks
+                .iter()
+                .for_each(|task| println!("  {} [{}] {}", task.id, task.status, task.description));
+        }
+    };
+    related("Depends On", &details.dependencies);
+    related("Required By", &details.dependents);
+    if let Some((done, total)) = task.checklist_progress() {
+        println!("\nChecklist [{}/{}]:", done, total);
+        task.checklist
+            .iter()
+            .enumerate()
+            .for_each(|(position, item)| {
+                let check = match item.done {
+                    true => "x",
+                    false => " ",
+                };
+                println!("  {}. [{}] {}", position + 1, check, item.text)
+            });
+    }
+    if !task.annotations.is_empty() {
+        println!("\nAnnotations:");
+        task.annotations
+            .iter()
+            .for_each(|annotation| println!("  {}: {}", at(annotation.at), annotation.text));
+    }
+    if !task.history.is_empty() {
+        println!("\nHistory:");
+        task.history
+            .iter()
+            .for_each(|change| println!("  {}: {} -> {}", at(change.at), change.from, change.to));
+    }
+    if !task.time_entries.is_empty() {
+        println!("\nTime Entries:");
+        task.time_entries.iter().for_each(|entry| {
+            // The date is only repeated for entries ending on another day
+            let end = match entry.end {
+                Some(end) if end.date_naive() == entry.start.date_naive() => {
+                    end.format("%H:%M").to_string()
+                }
+                Some(end) => end.format("%Y-%m-%d %H:%M").to_string(),
+                None => "running".to_string(),
+            };
+            println!(
+                "  {} - {}: {}",
+                at(entry.start),
+                end,
+                format_duration(entry.duration())
+            )
+        });
+    }
+}
+
+/// Plain values only: ids as numbers, statuses and estimates as on the command
+/// line, and durations in minutes
+pub fn show_task_details_json(details: &TaskDetails) {
+    let task = &details.task;
+    let ids =
+        |tasks: Vec<&Task>| -> Vec<u32> { tasks.iter().map(|task| task.id.value()).collect() };
+    let json = serde_json::json!({
+        "id": task.id.value(),
+        "description": task.description.to_string(),
+        "body": task.body,
+        "status": task.status.to_string(),
+        "created_at": task.created_at,
+        "updated_at": task.updated_at,
+        "completed_at": task.completed_at(),
+        "due": task.due,
+        "recur": task.recur.map(|recur| recur.to_string()),
+        "milestone": task.milestone,
+        "sprint": task.sprint,
+        "estimate": task.estimate.map(|estimate| estimate.to_string()),
+        "depends_on": task.depends_on.iter().map(|id| id.value()).collect::<Vec<u32>>(),
+        "blocked_by": ids(details
+            .dependencies
+            .iter()
+            .filter(|task| !task.status.is_closed())
+            .collect()),
+        "required_by": ids(details.dependents.iter().collect()),
+        "checklist": &task.checklist,
+        "annotations": task.annotations.iter().map(|annotation| serde_json::json!({
+            "at": annotation.at,
+            "text": annotation.text,
+        })).collect::<Vec<_>>(),
+        "history": task.history.iter().map(|change| serde_json::json!({
+            "at": change.at,
+            "from": change.from.to_string(),
+            "to": change.to.to_string(),
+        })).collect::<Vec<_>>(),
+        "time_entries": task.time_entries.iter().map(|entry| serde_json::json!({
+            "start": entry.start,
+            "end": entry.end,
+            "minutes": entry.duration().num_minutes(),
+        })).collect::<Vec<_>>(),
+        "tracked_minutes": task.tracked_time().num_minutes(),
+        "lead_time_minutes": task.lead_time().map(|time| time.num_minutes()),
+        "cycle_time_minutes": task.cycle_time().map(|time| time.num_minutes()),
+    });
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json).expect("tasks are always serializable")
+    );
 }
 
 pub fn show_added_task(task: &Task) {
@@ -23,8 +246,26 @@ pub fn show_updated_task(task: &Task) {
     );
 }
 
-pub fn show_deleted_task(r: &TaskId) {
-    println!("Successfully Deleted Task {}", r);
+pub fn show_updated_tasks(tasks: &[Task]) {
+    if tasks.is_empty() {
+        println!("No matching tasks");
+    }
+    tasks.iter().for_each(show_updated_task);
+}
+
+pub fn show_deleted_tasks(ids: &[TaskId]) {
+    if ids.is_empty() {
+        println!("No matching tasks");
+    }
+    ids.iter()
+        .for_each(|id| println!("Successfully Deleted Task {}", id));
+}
+
+pub fn show_updated_statuses(results: &[UpdateStatusResult]) {
+    if results.is_empty() {
+        println!("No matching tasks");
+    }
+    results.iter().for_each(show_updated_status);
 }
 
 pub fn show_updated_status(r: &UpdateStatusResult) {
@@ -35,6 +276,707 @@ New status: {}
 "#,
         r.task_id, r.old_status, r.new_status
     );
+    if !r.blocked_by.is_empty() {
+        let ids: Vec<String> = r.blocked_by.iter().map(|id| id.to_string()).collect();
+        println!(
+            "Warning: task is blocked by unfinished tasks: {}",
+            ids.join(", ")
+        );
+    }
+    if let Some(task) = &r.next_occurrence {
+        println!(
+            r#"Next Occurrence:
+{task}
+    "#
+        );
+    }
+}
+
+pub fn show_edited_task(result: &Option<EditResult>) {
+    let Some(result) = result else {
+        println!("No changes");
+        return;
+    };
+    if let Some(status_change) = &result.status_change {
+        show_updated_status(status_change);
+    }
+    show_updated_task(&result.task);
+}
+
+pub fn show_added_checklist_item(task: &Task) {
+    println!(
+        "Added item {} to the checklist of task {}",
+        task.checklist.len(),
+        task.id
+    );
+}
+
+pub fn show_annotated_task(task: &Task) {
+    println!(
+        "Annotated task {} ({} annotation(s))",
+        task.id,
+        task.annotations.len()
+    );
+}
+
+pub fn show_checked_item(result: &CheckResult) {
+    if let Some(status_change) = &result.status_change {
+        show_updated_status(status_change);
+    }
+    if let Some((done, total)) = result.task.checklist_progress() {
+        println!("Checklist of task {}: [{}/{}]", result.task.id, done, total);
+    }
+}
+
+pub fn show_started_timer(r: &StartTimerResult) {
+    if let Some(status_change) = &r.status_change {
+        show_updated_status(status_change);
+    }
+    println!("Started timer on task {}", r.task.id);
+}
+
+pub fn show_stopped_timer(r: &StopTimerResult) {
+    println!(
+        "Stopped timer on task {} after {}. Total tracked: {}",
+        r.task.id,
+        format_duration(r.elapsed),
+        format_duration(r.task.tracked_time())
+    );
+}
+
+pub fn show_logged_time(task: &Task) {
+    println!(
+        "Logged time on task {}. Total tracked: {}",
+        task.id,
+        format_duration(task.tracked_time())
+    );
+}
+
+pub fn show_timesheet(timesheet: &Timesheet) {
+    println!("Timesheet {} to {}", timesheet.from, timesheet.to);
+    let mut headers = timesheet.headers();
+    headers.push("Hours");
+    let mut rows: Vec<Vec<String>> = timesheet
+        .rows
+        .iter()
+        .map(|row| {
+            let mut cells = row.labels.clone();
+            cells.push(format_hours(row.duration));
+            cells
+        })
+        .collect();
+    let mut total_row = vec!["Total".to_string()];
+    total_row.resize(headers.len() - 1, String::new());
+    total_row.push(format_hours(timesheet.total));
+    rows.push(total_row);
+    show_table(&headers, &rows);
+}
+
+pub fn show_timesheet_csv(timesheet: &Timesheet) {
+    let mut headers = timesheet.headers();
+    headers.push("Hours");
+    println!("{}", headers.join(","));
+    timesheet.rows.iter().for_each(|row| {
+        let mut cells: Vec<String> = row.labels.iter().map(|label| csv_field(label)).collect();
+        cells.push(format_hours(row.duration));
+        println!("{}", cells.join(","));
+    });
+}
+
+/// Prints rows under headers with padded columns, the last one right aligned
+fn show_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect();
+    rows.iter().for_each(|row| {
+        row.iter().enumerate().for_each(|(i, cell)| {
+            widths[i] = widths[i].max(cell.chars().count());
+        })
+    });
+    let format_row = |cells: Vec<&str>| {
+        let last = cells.len() - 1;
+        cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| match i == last {
+                true => format!("{:>width$}", cell, width = widths[i]),
+                false => format!("{:<width$}", cell, width = widths[i]),
+            })
+            .collect::<Vec<String>>()
+            .join("  ")
+    };
+    println!("{}", format_row(headers.to_vec()));
+    println!(
+        "{}",
+        widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<String>>()
+            .join("  ")
+    );
+    rows.iter().for_each(|row| {
+        println!(
+            "{}",
+            format_row(row.iter().map(|cell| cell.as_str()).collect())
+        )
+    });
+}
+
+fn format_hours(duration: TimeDelta) -> String {
+    format!("{:.2}", duration.num_minutes() as f64 / 60.0)
+}
+
+fn csv_field(value: &str) -> String {
+    match value.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+pub fn show_estimate_report(report: &EstimateReport) {
+    if report.rows.is_empty() {
+        println!("No done tasks with estimates");
+        return;
+    }
+    let rows: Vec<Vec<String>> = report
+        .rows
+        .iter()
+        .map(|row| {
+            let ratio = match row.estimate {
+                Estimate::Time { minutes } => {
+                    format_ratio(row.actual, TimeDelta::minutes(minutes as i64))
+                }
+                Estimate::Points { points } => format_hours_per_point(row.actual, points),
+            };
+            vec![
+                row.task.id.to_string(),
+                row.task.description.to_string(),
+                row.estimate.to_string(),
+                format_duration(row.actual),
+                ratio,
+            ]
+        })
+        .collect();
+    show_table(
+        &["Id", "Description", "Estimate", "Tracked", "Ratio"],
+        &rows,
+    );
+    if report.estimated_time > TimeDelta::zero() {
+        println!(
+            "Time estimates: {} estimated, {} tracked ({})",
+            format_duration(report.estimated_time),
+            format_duration(report.actual_for_time),
+            format_ratio(report.actual_for_time, report.estimated_time)
+        );
+    }
+    if report.estimated_points > 0 {
+        println!(
+            "Point estimates: {}pt estimated, {} tracked ({})",
+            report.estimated_points,
+            format_duration(report.actual_for_points),
+            format_hours_per_point(report.actual_for_points, report.estimated_points)
+        );
+    }
+}
+
+fn format_ratio(actual: TimeDelta, estimate: TimeDelta) -> String {
+    match estimate.num_minutes() {
+        0 => "-".to_string(),
+        minutes => format!("{:.2}x", actual.num_minutes() as f64 / minutes as f64),
+    }
+}
+
+fn format_hours_per_point(actual: TimeDelta, points: u32) -> String {
+    match points {
+        0 => "-".to_string(),
+        points => format!("{}h/pt", format_hours(actual / points as i32)),
+    }
+}
+
+pub fn show_added_milestone(milestone: &Milestone) {
+    match milestone.target {
+        Some(target) => println!("Added Milestone {} targeting {}", milestone.name, target),
+        None => println!("Added Milestone {}", milestone.name),
+    }
+}
+
+pub fn show_roadmap(roadmap: &Vec<MilestoneProgress>) {
+    if roadmap.is_empty() {
+        println!("No milestones");
+        return;
+    }
+    let rows: Vec<Vec<String>> = roadmap
+        .iter()
+        .map(|progress| {
+            vec![
+                progress.milestone.name.clone(),
+                progress
+                    .milestone
+                    .target
+                    .map(|target| target.to_string())
+                    .unwrap_or("-".to_string()),
+                format!("{}%", progress.percent()),
+                format!("{}/{}", progress.done, progress.total),
+                progress.open.to_string(),
+                progress.schedule.to_string(),
+            ]
+        })
+        .collect();
+    show_table(
+        &["Milestone", "Target", "Done", "Tasks", "Open", "Status"],
+        &rows,
+    );
+}
+
+pub fn show_added_sprint(sprint: &Sprint) {
+    print!(
+        "Added Sprint {} from {} to {}",
+        sprint.name, sprint.start, sprint.end
+    );
+    match &sprint.capacity {
+        Some(capacity) => println!(" with a capacity of {}", capacity),
+        None => println!(),
+    }
+}
+
+pub fn show_sprint_plan(plan: &SprintPlan) {
+    println!(
+        "Planned {} task(s) into Sprint {}",
+        plan.planned.len(),
+        plan.sprint.name
+    );
+    match &plan.sprint.capacity {
+        Some(capacity) => println!("Load: {} / {}", plan.load, capacity),
+        None => println!("Load: {}", plan.load),
+    }
+    if plan.is_over_capacity() {
+        println!("Warning: Sprint {} is over capacity", plan.sprint.name);
+    }
+}
+
+pub fn show_sprint_board(board: &SprintBoard) {
+    println!(
+        "Sprint {} ({} - {})",
+        board.sprint.name, board.sprint.start, board.sprint.end
+    );
+    board.columns.iter().for_each(|(status, tasks)| {
+        println!("\n{} ({})", status, tasks.len());
+        tasks.iter().for_each(|task| match task.estimate {
+            Some(estimate) => println!("  {}: {} [{}]", task.id, task.description, estimate),
+            None => println!("  {}: {}", task.id, task.description),
+        });
+    });
+}
+
+pub fn show_sprint_close_report(report: &SprintCloseReport) {
+    println!("Closed Sprint {}", report.sprint.name);
+    println!(
+        "Completed: {}/{} task(s), {} of {}",
+        report.completed, report.committed, report.completed_estimate, report.committed_estimate
+    );
+    let Some(next) = &report.next_sprint else {
+        return;
+    };
+    if report.carried_over.is_empty() {
+        return;
+    }
+    let ids: Vec<String> = report
+        .carried_over
+        .iter()
+        .map(|id| id.to_string())
+        .collect();
+    println!("Carried over to Sprint {}: {}", next, ids.join(", "));
+}
+
+pub fn show_chart(series: &ChartSeries) {
+    println!("{}", series.title);
+    if series.points.is_empty() {
+        println!("No data");
+        return;
+    }
+    match series.kind {
+        ChartKind::Burndown => show_column_chart(series),
+        ChartKind::Throughput => show_bar_chart(series),
+    }
+}
+
+const CHART_HEIGHT: u32 = 10;
+const CHART_WIDTH: u32 = 40;
+const VERTICAL_BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const HORIZONTAL_BLOCKS: [char; 9] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+
+/// One column per point, drawn with eighth blocks
+fn show_column_chart(series: &ChartSeries) {
+    let max = series.max().max(1);
+    let label_width = max.to_string().len();
+    let levels: Vec<u32> = series
+        .points
+        .iter()
+        .map(|(_, value)| value * CHART_HEIGHT * 8 / max)
+        .collect();
+    for row in (0..CHART_HEIGHT).rev() {
+        let label = match row {
+            row if row == CHART_HEIGHT - 1 => max.to_string(),
+            0 => "0".to_string(),
+            _ => String::new(),
+        };
+        let line: String = levels
+            .iter()
+            .map(|level| VERTICAL_BLOCKS[level.saturating_sub(row * 8).min(8) as usize])
+            .collect();
+        println!("{:>label_width$} │{}", label, line);
+    }
+    println!("{:>label_width$} └{}", "", "─".repeat(series.points.len()));
+    let first = series.points[0].0.to_string();
+    let last = series.points[series.points.len() - 1].0.to_string();
+    let gap = series.points.len().saturating_sub(first.len() + last.len());
+    match gap {
+        0 => println!("{:>label_width$}  {} - {}", "", first, last),
+        gap => println!("{:>label_width$}  {}{}{}", "", first, " ".repeat(gap), last),
+    }
+}
+
+/// One row per point, the longest bar spanning the chart width
+fn show_bar_chart(series: &ChartSeries) {
+    let max = series.max().max(1);
+    series.points.iter().for_each(|(date, value)| {
+        let eighths = value * CHART_WIDTH * 8 / max;
+        let mut bar = "█".repeat((eighths / 8) as usize);
+        if let partial @ 1.. = eighths % 8 {
+            bar.push(HORIZONTAL_BLOCKS[partial as usize]);
+        }
+        println!("{} │{} {}", date, bar, value);
+    });
+}
+
+pub fn show_forecast(forecast: &Forecast) {
+    if forecast.remaining == 0 {
+        println!("Nothing left to do for {}", forecast.scope);
+        return;
+    }
+    let completed: u32 = forecast.samples.iter().sum();
+    println!(
+        "Forecast for {}: {} open task(s)",
+        forecast.scope, forecast.remaining
+    );
+    println!(
+        "Based on {} weeks of history ({:.1} tasks done per week), {} simulations",
+        forecast.samples.len(),
+        completed as f64 / forecast.samples.len() as f64,
+        FORECAST_TRIALS
+    );
+    forecast.percentiles.iter().for_each(|percentile| {
+        println!(
+            "  {}%: {} ({} weeks)",
+            percentile.confidence, percentile.date, percentile.weeks
+        )
+    });
+}
+
+pub fn show_stats(stats: &Stats) {
+    println!("Tasks: {}", stats.total);
+    let rows: Vec<Vec<String>> = stats
+        .by_status
+        .iter()
+        .map(|count| vec![count.status.clone(), count.count.to_string()])
+        .collect();
+    show_table(&["Status", "Tasks"], &rows);
+    println!();
+    let rows: Vec<Vec<String>> = stats
+        .weeks
+        .iter()
+        .map(|week| {
+            vec![
+                week.week.to_string(),
+                week.created.to_string(),
+                week.completed.to_string(),
+            ]
+        })
+        .collect();
+    show_table(&["Week", "Created", "Completed"], &rows);
+    println!();
+    let format_average =
+        |average: Option<TimeDelta>| average.map(format_duration).unwrap_or("-".to_string());
+    println!(
+        "Average age of open tasks: {}",
+        format_average(stats.average_open_age)
+    );
+    if let Some(oldest) = &stats.oldest_open_task {
+        println!(
+            "Oldest open task: {} {} ({})",
+            oldest.id,
+            oldest.description,
+            format_duration(oldest.age)
+        );
+    }
+    println!(
+        "Average lead time: {}",
+        format_average(stats.average_lead_time)
+    );
+}
+
+pub fn show_stats_json(stats: &Stats) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(stats).expect("stats are always serializable")
+    );
+}
+
+const HEATMAP_BLOCKS: [char; 5] = ['·', '░', '▒', '▓', '█'];
+const HEATMAP_COLORS: [u8; 5] = [238, 22, 28, 34, 40];
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// See https://no-color.org
+fn use_color() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+pub fn show_heatmap(heatmap: &Heatmap) {
+    match heatmap.metric {
+        HeatmapMetric::Completed => println!(
+            "{} task(s) completed in the last {} weeks",
+            heatmap.total(),
+            HEATMAP_WEEKS
+        ),
+        HeatmapMetric::Hours => println!(
+            "{}h tracked in the last {} weeks",
+            format_hours(TimeDelta::minutes(heatmap.total() as i64)),
+            HEATMAP_WEEKS
+        ),
+    }
+    let color = use_color();
+    let cell = |level: u32| {
+        let block = HEATMAP_BLOCKS[level as usize];
+        match color {
+            true => format!(
+                "\x1b[38;5;{}m{}\x1b[0m",
+                HEATMAP_COLORS[level as usize], block
+            ),
+            false => block.to_string(),
+        }
+    };
+    let mondays: Vec<_> = (0..HEATMAP_WEEKS)
+        .map(|week| heatmap.start + Days::new(7 * week))
+        .collect();
+    let mut months = vec![' '; mondays.len() + 3];
+    // The first column only gets a label when it starts its month
+    let mut previous_month = (heatmap.start.day() > 7).then(|| heatmap.start.month());
+    for (i, monday) in mondays.iter().enumerate() {
+        if previous_month != Some(monday.month()) && months[i.saturating_sub(1)] == ' ' {
+            let month = MONTHS[monday.month0() as usize];
+            months.splice(i..i + 3, month.chars());
+        }
+        previous_month = Some(monday.month());
+    }
+    println!("    {}", months.iter().collect::<String>().trim_end());
+    for (weekday, name) in WEEKDAYS.iter().enumerate() {
+        let row: String = mondays
+            .iter()
+            .map(|monday| *monday + Days::new(weekday as u64))
+            .filter(|day| *day <= heatmap.today)
+            .map(|day| cell(heatmap.level(heatmap.value(day).unwrap_or(0))))
+            .collect();
+        println!("{} {}", name, row);
+    }
+    let legend: Vec<String> = (0..HEATMAP_BLOCKS.len() as u32).map(cell).collect();
+    println!("    Less {} More", legend.join(""));
+}
+
+pub fn show_standup(standup: &Standup, format: StandupFormat) {
+    let heading = |title: String| match format {
+        StandupFormat::Markdown => format!("**{}**", title),
+        StandupFormat::Slack => format!("*{}*", title),
+        StandupFormat::Plain => format!("{}:", title),
+    };
+    let bullet = match format {
+        StandupFormat::Markdown => "- ",
+        StandupFormat::Slack => "• ",
+        StandupFormat::Plain => "  - ",
+    };
+    let show_section = |title: String, lines: Vec<String>| {
+        println!("{}", heading(title));
+        match lines.is_empty() {
+            true => println!("{}Nothing", bullet),
+            false => lines.iter().for_each(|line| println!("{}{}", bullet, line)),
+        }
+        println!();
+    };
+    let task_line = |task: &Task| format!("#{} {}", task.id, task.description);
+
+    match format {
+        StandupFormat::Markdown => println!("## Standup {}\n", standup.today),
+        StandupFormat::Slack => println!("*Standup {}*\n", standup.today),
+        StandupFormat::Plain => println!("Standup {}\n", standup.today),
+    }
+    show_section(
+        format!("Done since {}", standup.since.format("%a %Y-%m-%d")),
+        standup.done.iter().map(|task| task_line(task)).collect(),
+    );
+    show_section(
+        "In progress".to_string(),
+        standup
+            .in_progress
+            .iter()
+            .map(|task| task_line(task))
+            .collect(),
+    );
+    show_section(
+        "Blocked".to_string(),
+        standup
+            .blocked
+            .iter()
+            .map(|blocked| match blocked.blocked_by.is_empty() {
+                true => task_line(blocked.task),
+                false => {
+                    let ids: Vec<String> = blocked
+                        .blocked_by
+                        .iter()
+                        .map(|id| format!("#{}", id))
+                        .collect();
+                    format!(
+                        "{} (waiting on {})",
+                        task_line(blocked.task),
+                        ids.join(", ")
+                    )
+                }
+            })
+            .collect(),
+    );
+}
+
+pub fn show_journal(days: &Vec<JournalDay>) {
+    if days.is_empty() {
+        println!("Nothing completed");
+        return;
+    }
+    days.iter().for_each(|day| {
+        println!("{}", day.date.format("%A %Y-%m-%d"));
+        day.completed
+            .iter()
+            .for_each(|task| println!("  Done {}: {}", task.id, task.description));
+        if !day.changes.is_empty() {
+            println!("  Changes:");
+        }
+        day.changes.iter().for_each(|change| {
+            let time = change.at.format("%H:%M");
+            match &change.kind {
+                ChangeKind::Created => println!(
+                    "    {} created {}: {}",
+                    time, change.task.id, change.task.description
+                ),
+                ChangeKind::Status { from, to } => {
+                    println!("    {} {}: {} -> {}", time, change.task.id, from, to)
+                }
+                ChangeKind::Field { field, value } => match (*field, value) {
+                    ("body", _) => println!("    {} {}: body edited", time, change.task.id),
+                    (field, Some(value)) => {
+                        println!(
+                            "    {} {}: {} set to {}",
+                            time, change.task.id, field, value
+                        )
+                    }
+                    (field, None) => println!("    {} {}: {} cleared", time, change.task.id, field),
+                },
+                ChangeKind::TimerStarted => {
+                    println!("    {} {}: timer started", time, change.task.id)
+                }
+                ChangeKind::TimeLogged { duration } => println!(
+                    "    {} {}: {} tracked",
+                    time,
+                    change.task.id,
+                    format_duration(*duration)
+                ),
+                ChangeKind::Annotated { text } => {
+                    println!("    {} {}: annotated \"{}\"", time, change.task.id, text)
+                }
+            }
+        });
+        println!();
+    });
+}
+
+pub fn show_search_results(results: &[SearchResult]) {
+    if results.is_empty() {
+        println!("No matching tasks");
+        return;
+    }
+    let color = use_color();
+    results.iter().for_each(|result| {
+        let text = searchable_text(result.task);
+        // The description always, then the lines of the body and annotations that matched
+        let mut line_start = 0;
+        for (number, line) in text.split('\n').enumerate() {
+            let line_end = line_start + line.len();
+            let ranges: Vec<_> = result
+                .highlights
+                .iter()
+                .filter(|range| range.start >= line_start && range.end <= line_end)
+                .collect();
+            let mut highlighted = String::new();
+            let mut end = line_start;
+            for range in &ranges {
+                highlighted.push_str(&text[end..range.start]);
+                let word = &text[(*range).clone()];
+                match color {
+                    true => highlighted.push_str(&format!("\x1b[1;33m{}\x1b[0m", word)),
+                    false => highlighted.push_str(&format!("[{}]", word)),
+                }
+                end = range.end;
+            }
+            highlighted.push_str(&text[end..line_end]);
+            match number {
+                0 => println!(
+                    "{:>3}% {} ({}): {}",
+                    (result.score * 100.0).round(),
+                    result.task.id,
+                    result.task.status,
+                    highlighted
+                ),
+                _ if !ranges.is_empty() => println!("       ...{}", highlighted.trim()),
+                _ => (),
+            }
+            line_start = line_end + 1;
+        }
+    });
+}
+
+pub fn show_recurring_series(series: &Vec<RecurringSeries>) {
+    if series.is_empty() {
+        println!("No recurring tasks");
+    }
+    series.iter().for_each(|s| {
+        println!(
+            "Series {}: {} ({}), next task {} due {}, {} done",
+            s.series,
+            s.next.description,
+            s.rule,
+            s.next.id,
+            s.next
+                .due
+                .map(|due| due.to_string())
+                .unwrap_or("-".to_string()),
+            s.completed
+        )
+    });
+}
+
+pub fn show_stopped_series(series: &TaskId) {
+    println!("Stopped Recurring Series {}", series);
+}
+
+pub fn show_next_task(task: &Option<&Task>) {
+    match task {
+        Some(task) => println!(
+            r#"Next Task:
+{task}
+    "#
+        ),
+        None => println!("No ready tasks"),
+    }
 }
 
 pub fn show_help() {
@@ -45,19 +987,109 @@ rtask add "Buy groceries"
 
 # Updating and deleting tasks
 rtask update 1 "Buy groceries and cook dinner"
+rtask edit 1
+rtask add "Plan the release" --body $'## Steps\n- freeze\n- tag'
+rtask annotate 1 "called vendor, waiting"
+rtask check add 1 "tag release"
+rtask check done 1 2
 rtask delete 1
 
 # Marking a task as in progress or done
 rtask mark-in-progress 1
 rtask mark-done 1
 
+# Moving a task to any status allowed by the workflow
+rtask set-status 1 in_review
+
 # Listing all tasks
 rtask list
 
+# Showing every field of a task, or the same as JSON
+rtask show 1
+rtask show 1 --json
+
 # Listing tasks by status
 rtask list done
 rtask list todo
 rtask list in-progress
+
+# Filtering with field:value conditions combined with and, or, not and parentheses
+# Fields: id, status, desc, due, created, updated, completed, milestone, sprint, is
+# Operators: : != < <= > >= and ~ (contains), dates accept weekdays and none
+rtask list 'status:todo and (due<friday or desc~"login") and not is:blocked'
+rtask set-status --where 'milestone:v1.0 and status:in_review' done
+rtask delete --where 'is:closed and completed<2026-01-01'
+rtask mark-done 1,4,7-12
+
+# Sorting by several keys (+ ascending, - descending) and paging
+rtask list --sort due+,estimate-,id --limit 10 --offset 10
+rtask list done --sort completed --reverse
+
+# Task dependencies
+rtask depend 2 --on 1
+rtask list --blocked
+rtask list --ready
+rtask next
+
+# Time tracking: one running timer at a time, manual entries with --on
+rtask start 1
+rtask stop
+rtask log-time 1 1h30m --on yesterday
+
+# Timesheets for the current week or a date range, by day or task, as a table or CSV
+rtask timesheet --week
+rtask timesheet --from 2026-10-01 --to 2026-10-31 --by task --csv
+
+# Estimates in hours or story points, compared to tracked time once done
+rtask estimate 1 3h
+rtask estimate 2 5pt
+rtask report estimates
+
+# Milestones with progress against their target dates
+rtask milestone add v1.0 --target 2026-12-01
+rtask milestone assign 1 v1.0
+rtask add "Write release notes" --milestone v1.0
+rtask roadmap
+
+# Sprints with an optional capacity, planned from the backlog and closed with carry-over
+rtask sprint add s1 --start mon --capacity 20pt
+rtask sprint plan s1 1 2 3
+rtask sprint current
+rtask sprint close
+
+# Charts in the terminal, optionally exported as CSV or SVG
+rtask chart burndown --milestone v1.0
+rtask chart throughput --weeks 12 --export throughput.svg
+
+# Completion dates forecast from the weekly throughput of past weeks
+rtask forecast --milestone v1.0
+rtask forecast --sprint s1 --weeks 8
+rtask forecast --where 'status:todo and desc~"login"'
+
+# Statistics as tables or JSON
+rtask stats
+rtask stats --weeks 4 --json
+
+# Heatmap of completed tasks, or tracked hours, per day over the last 52 weeks
+rtask heatmap
+rtask heatmap --hours
+
+# Standup report since the previous working day, for chat
+rtask standup
+rtask standup --since fri --format slack
+
+# Log of completed tasks per day, with every change using --changes
+rtask log --since 2026-10-01
+rtask log --changes
+
+# Fuzzy search over descriptions, bodies and annotations, best matches first
+rtask search "grocries"
+rtask search "dog walk" --limit 5
+
+# Recurring tasks (daily, weekly, weekdays, monthly, monthly:<day>, every:<days>)
+rtask add "Weekly report" --due mon --recur weekly
+rtask recur list
+rtask recur stop 1
 "#
     );
 }
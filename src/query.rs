@@ -0,0 +1,72 @@
+use std::collections::HashSet;
+
+use crate::{
+    command::DependencyFilter,
+    dependency,
+    filter::Filter,
+    sort::SortOrder,
+    task::{Task, TaskId, TaskStatus},
+};
+
+/// Tasks to read from storage. Backends narrow down the candidates as best
+/// they can, e.g. with an index on `ids`, and leave the rest to `run`
+#[derive(Debug, Default)]
+pub struct TaskQuery {
+    /// Only these tasks, in this order unless sorted
+    pub ids: Option<Vec<TaskId>>,
+    pub status: Option<TaskStatus>,
+    pub filter: Option<Filter>,
+    pub dependency: Option<DependencyFilter>,
+    pub sort: Option<SortOrder>,
+    pub reverse: bool,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+impl TaskQuery {
+    pub fn ids(ids: Vec<TaskId>) -> Self {
+        TaskQuery {
+            ids: Some(ids),
+            ..Default::default()
+        }
+    }
+
+    pub fn filter(filter: Filter) -> Self {
+        TaskQuery {
+            filter: Some(filter),
+            ..Default::default()
+        }
+    }
+
+    /// Keeps the `candidates` matching the query, then sorts and pages them.
+    /// `tasks` holds every task, for conditions on dependencies
+    pub fn run<'a>(&self, candidates: Vec<&'a Task>, tasks: &'a [Task]) -> Vec<&'a Task> {
+        let ids: Option<HashSet<&TaskId>> = self.ids.as_ref().map(|ids| ids.iter().collect());
+        let mut matches: Vec<&Task> = candidates
+            .into_iter()
+            .filter(|task| ids.as_ref().is_none_or(|ids| ids.contains(&task.id)))
+            .filter(|task| self.status.is_none_or(|status| status == task.status))
+            .filter(|task| {
+                self.filter
+                    .as_ref()
+                    .is_none_or(|filter| filter.matches(task, tasks))
+            })
+            .filter(|task| match self.dependency {
+                None => true,
+                Some(DependencyFilter::Blocked) => dependency::is_blocked(task, tasks),
+                Some(DependencyFilter::Ready) => dependency::is_ready(task, tasks),
+            })
+            .collect();
+        if let Some(sort) = &self.sort {
+            sort.sort(&mut matches);
+        }
+        if self.reverse {
+            matches.reverse();
+        }
+        matches
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
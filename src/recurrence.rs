@@ -0,0 +1,86 @@
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::{datetime::last_day_of_month, Error, Result};
+
+/// Longest interval of an `every:<days>` rule, ten years
+pub const MAX_RECURRENCE_DAYS: u32 = 3650;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub enum RecurrenceRule {
+    Daily,
+    Weekly,
+    Weekdays,
+    Monthly { day: u32 },
+    EveryNDays { days: u32 },
+}
+
+impl std::fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecurrenceRule::Daily => write!(f, "daily"),
+            RecurrenceRule::Weekly => write!(f, "weekly"),
+            RecurrenceRule::Weekdays => write!(f, "weekdays"),
+            RecurrenceRule::Monthly { day } => write!(f, "monthly:{}", day),
+            RecurrenceRule::EveryNDays { days } => write!(f, "every:{}", days),
+        }
+    }
+}
+
+impl RecurrenceRule {
+    /// `anchor` provides the day of month for a plain `monthly` rule
+    pub fn parse(s: &str, anchor: NaiveDate) -> Result<Self> {
+        let invalid_rule = || Error::InvalidRecurrence {
+            rule: s.to_string(),
+        };
+        match s.split_once(':') {
+            None => match s {
+                "daily" => Ok(RecurrenceRule::Daily),
+                "weekly" => Ok(RecurrenceRule::Weekly),
+                "weekdays" => Ok(RecurrenceRule::Weekdays),
+                "monthly" => Ok(RecurrenceRule::Monthly { day: anchor.day() }),
+                _ => Err(invalid_rule()),
+            },
+            Some((kind, n)) => {
+                let n = n.parse::<u32>().map_err(|_| invalid_rule())?;
+                match kind {
+                    "monthly" if (1..=31).contains(&n) => Ok(RecurrenceRule::Monthly { day: n }),
+                    "every" if (1..=MAX_RECURRENCE_DAYS).contains(&n) => {
+                        Ok(RecurrenceRule::EveryNDays { days: n })
+                    }
+                    _ => Err(invalid_rule()),
+                }
+            }
+        }
+    }
+
+    /// Fails when the next date is past the last one chrono can represent
+    pub fn next_due(&self, from: NaiveDate) -> Result<NaiveDate> {
+        let next = match self {
+            RecurrenceRule::Daily => from.checked_add_days(Days::new(1)),
+            RecurrenceRule::Weekly => from.checked_add_days(Days::new(7)),
+            RecurrenceRule::EveryNDays { days } => from.checked_add_days(Days::new(*days as u64)),
+            RecurrenceRule::Weekdays => {
+                let mut next = from.checked_add_days(Days::new(1));
+                while let Some(date) =
+                    next.filter(|date| matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+                {
+                    next = date.checked_add_days(Days::new(1));
+                }
+                next
+            }
+            RecurrenceRule::Monthly { day } => {
+                let (year, month) = match from.month() {
+                    12 => (from.year() + 1, 1),
+                    month => (from.year(), month + 1),
+                };
+                let day = (*day).min(last_day_of_month(year, month));
+                NaiveDate::from_ymd_opt(year, month, day)
+            }
+        };
+        next.ok_or_else(|| Error::NoNextOccurrence {
+            rule: self.to_string(),
+            from: from.to_string(),
+        })
+    }
+}
@@ -0,0 +1,54 @@
+use chrono::TimeDelta;
+
+use crate::task::{Estimate, Task, TaskStatus};
+
+pub struct EstimateReportRow<'a> {
+    pub task: &'a Task,
+    pub estimate: Estimate,
+    pub actual: TimeDelta,
+}
+
+/// Estimates against tracked time for done tasks, with totals kept apart
+/// for time estimates and story points
+pub struct EstimateReport<'a> {
+    pub rows: Vec<EstimateReportRow<'a>>,
+    pub estimated_time: TimeDelta,
+    pub actual_for_time: TimeDelta,
+    pub estimated_points: u32,
+    pub actual_for_points: TimeDelta,
+}
+
+pub fn build_estimate_report(tasks: &[Task]) -> EstimateReport<'_> {
+    let rows: Vec<EstimateReportRow> = tasks
+        .iter()
+        .filter(|task| task.status == TaskStatus::Done)
+        .filter_map(|task| {
+            task.estimate.map(|estimate| EstimateReportRow {
+                task,
+                estimate,
+                actual: task.tracked_time(),
+            })
+        })
+        .collect();
+    let mut report = EstimateReport {
+        rows: vec![],
+        estimated_time: TimeDelta::zero(),
+        actual_for_time: TimeDelta::zero(),
+        estimated_points: 0,
+        actual_for_points: TimeDelta::zero(),
+    };
+    for row in &rows {
+        match row.estimate {
+            Estimate::Time { minutes } => {
+                report.estimated_time += TimeDelta::minutes(minutes as i64);
+                report.actual_for_time += row.actual;
+            }
+            Estimate::Points { points } => {
+                report.estimated_points = report.estimated_points.saturating_add(points);
+                report.actual_for_points += row.actual;
+            }
+        }
+    }
+    report.rows = rows;
+    report
+}
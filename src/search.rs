@@ -0,0 +1,168 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use crate::task::{Task, TaskId};
+
+/// Share of the query trigrams a task must contain to match
+const MIN_SCORE: f64 = 0.5;
+/// Trigram similarity above which a word of a task is highlighted
+const MIN_WORD_SIMILARITY: f64 = 0.5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub id: TaskId,
+    /// From 0 to 1, the share of the query trigrams found in the task
+    pub score: f64,
+}
+
+pub struct SearchResult<'a> {
+    pub task: &'a Task,
+    pub score: f64,
+    /// Byte ranges of the matched words in the searchable text of the task
+    pub highlights: Vec<Range<usize>>,
+}
+
+/// The text a task is searched by: its description on the first line, then
+/// its body and annotations
+pub fn searchable_text(task: &Task) -> String {
+    let mut text = task.description.to_string();
+    if let Some(body) = &task.body {
+        text.push('\n');
+        text.push_str(body);
+    }
+    for annotation in &task.annotations {
+        text.push('\n');
+        text.push_str(&annotation.text);
+    }
+    text
+}
+
+/// Lowercase alphanumeric words with their byte range in `text`
+fn words(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut words = vec![];
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(from)) => {
+                words.push((from..i, text[from..i].to_lowercase()));
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    words
+}
+
+/// Trigrams of a word padded with spaces, so that short words and word
+/// boundaries count too: `dog` gives `  d`, ` do`, `dog` and `og `
+fn word_trigrams(word: &str) -> HashSet<String> {
+    let chars: Vec<char> = format!("  {} ", word).chars().collect();
+    chars
+        .windows(3)
+        .map(|window| window.iter().collect())
+        .collect()
+}
+
+fn trigrams(text: &str) -> HashSet<String> {
+    words(text)
+        .iter()
+        .flat_map(|(_, word)| word_trigrams(word))
+        .collect()
+}
+
+/// Dice coefficient of the trigrams of two words
+fn word_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (word_trigrams(a), word_trigrams(b));
+    2.0 * a.intersection(&b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+/// Byte ranges of the words of `text` that look like one of the query words
+pub fn highlights(text: &str, query: &str) -> Vec<Range<usize>> {
+    let query_words: Vec<String> = words(query).into_iter().map(|(_, word)| word).collect();
+    words(text)
+        .into_iter()
+        .filter(|(_, word)| {
+            query_words.iter().any(|query_word| {
+                word.contains(query_word.as_str())
+                    || word_similarity(word, query_word) >= MIN_WORD_SIMILARITY
+            })
+        })
+        .map(|(range, _)| range)
+        .collect()
+}
+
+/// Inverted index from trigrams to tasks. Matching on trigrams rather than
+/// whole words tolerates typos, and ignores word order
+#[derive(Default)]
+pub struct SearchIndex {
+    documents: HashMap<TaskId, HashSet<String>>,
+    postings: HashMap<String, HashSet<TaskId>>,
+}
+
+impl SearchIndex {
+    pub fn new(tasks: &[Task]) -> Self {
+        let mut index = SearchIndex::default();
+        tasks.iter().for_each(|task| index.insert(task));
+        index
+    }
+
+    /// Adds `task`, replacing what was indexed for its id before
+    pub fn insert(&mut self, task: &Task) {
+        self.remove(&task.id);
+        let trigrams = trigrams(&searchable_text(task));
+        for trigram in &trigrams {
+            self.postings
+                .entry(trigram.clone())
+                .or_default()
+                .insert(task.id);
+        }
+        self.documents.insert(task.id, trigrams);
+    }
+
+    pub fn remove(&mut self, id: &TaskId) {
+        let Some(trigrams) = self.documents.remove(id) else {
+            return;
+        };
+        for trigram in trigrams {
+            if let Some(ids) = self.postings.get_mut(&trigram) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.postings.remove(&trigram);
+                }
+            }
+        }
+    }
+
+    /// Best matches first. Among equal scores, tasks with less unrelated text come first
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let query = trigrams(query);
+        if query.is_empty() {
+            return vec![];
+        }
+        let mut matches: HashMap<TaskId, usize> = HashMap::new();
+        for trigram in &query {
+            for id in self.postings.get(trigram).into_iter().flatten() {
+                *matches.entry(*id).or_default() += 1;
+            }
+        }
+        let mut hits: Vec<(SearchHit, f64)> = matches
+            .into_iter()
+            .map(|(id, count)| {
+                let precision = count as f64 / self.documents[&id].len() as f64;
+                let score = count as f64 / query.len() as f64;
+                (SearchHit { id, score }, precision)
+            })
+            .filter(|(hit, _)| hit.score >= MIN_SCORE)
+            .collect();
+        hits.sort_by(|(a, a_precision), (b, b_precision)| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b_precision.total_cmp(a_precision))
+                .then(a.id.cmp(&b.id))
+        });
+        hits.into_iter().map(|(hit, _)| hit).collect()
+    }
+}
@@ -0,0 +1,163 @@
+use std::cmp::Ordering;
+
+use serde::Deserialize;
+
+use crate::{
+    task::{Estimate, Task, TaskStatus},
+    Error, Result,
+};
+
+const SORT_FIELDS: &str =
+    "id, status, desc, due, created, updated, completed, milestone, sprint, estimate";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortField {
+    Id,
+    Status,
+    Description,
+    Due,
+    Created,
+    Updated,
+    Completed,
+    Milestone,
+    Sprint,
+    Estimate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+/// Keys such as `due+,id-`: ascending unless followed by `-`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct SortOrder(pub Vec<SortKey>);
+
+impl TryFrom<String> for SortOrder {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        SortOrder::from_str(&value)
+    }
+}
+
+impl SortOrder {
+    pub fn from_str(s: &str) -> Result<Self> {
+        let keys = s
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(|key| {
+                let (name, descending) = match key.strip_suffix('-') {
+                    Some(name) => (name, true),
+                    None => (key.strip_suffix('+').unwrap_or(key), false),
+                };
+                let field = match name.to_lowercase().as_str() {
+                    "id" => SortField::Id,
+                    "status" => SortField::Status,
+                    "desc" | "description" => SortField::Description,
+                    "due" => SortField::Due,
+                    "created" => SortField::Created,
+                    "updated" => SortField::Updated,
+                    "completed" => SortField::Completed,
+                    "milestone" => SortField::Milestone,
+                    "sprint" => SortField::Sprint,
+                    "estimate" => SortField::Estimate,
+                    _ => {
+                        return Err(Error::InvalidSortKey {
+                            key: key.to_string(),
+                            expected: SORT_FIELDS.to_string(),
+                        })
+                    }
+                };
+                Ok(SortKey { field, descending })
+            })
+            .collect::<Result<Vec<SortKey>>>()?;
+        Ok(SortOrder(keys))
+    }
+
+    /// Ties are broken by id. Tasks without a value for a key come last,
+    /// whatever its direction
+    pub fn sort(&self, tasks: &mut [&Task]) {
+        tasks.sort_by(|a, b| {
+            self.0
+                .iter()
+                .map(|key| {
+                    let ordering = match key.field {
+                        SortField::Id => Some(a.id.cmp(&b.id)),
+                        SortField::Status => {
+                            Some(status_rank(a.status).cmp(&status_rank(b.status)))
+                        }
+                        SortField::Description => Some(
+                            a.description
+                                .to_string()
+                                .to_lowercase()
+                                .cmp(&b.description.to_string().to_lowercase()),
+                        ),
+                        SortField::Due => compare_optional(a.due, b.due),
+                        SortField::Created => Some(a.created_at.cmp(&b.created_at)),
+                        SortField::Updated => Some(a.updated_at.cmp(&b.updated_at)),
+                        SortField::Completed => {
+                            compare_optional(a.completed_at(), b.completed_at())
+                        }
+                        SortField::Milestone => {
+                            compare_optional(a.milestone.as_ref(), b.milestone.as_ref())
+                        }
+                        SortField::Sprint => compare_optional(a.sprint.as_ref(), b.sprint.as_ref()),
+                        SortField::Estimate => compare_optional(
+                            a.estimate.map(estimate_rank),
+                            b.estimate.map(estimate_rank),
+                        ),
+                    };
+                    match (ordering, key.descending) {
+                        (Some(ordering), true) => ordering.reverse(),
+                        (Some(ordering), false) => ordering,
+                        (None, _) => missing_last(a, b, key.field),
+                    }
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| a.id.cmp(&b.id))
+        });
+    }
+}
+
+/// `None` when one of the values is missing
+fn compare_optional<T: Ord>(a: Option<T>, b: Option<T>) -> Option<Ordering> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.cmp(&b)),
+        _ => None,
+    }
+}
+
+fn missing_last(a: &Task, b: &Task, field: SortField) -> Ordering {
+    let missing = |task: &Task| match field {
+        SortField::Due => task.due.is_none(),
+        SortField::Completed => task.completed_at().is_none(),
+        SortField::Milestone => task.milestone.is_none(),
+        SortField::Sprint => task.sprint.is_none(),
+        SortField::Estimate => task.estimate.is_none(),
+        _ => false,
+    };
+    missing(a).cmp(&missing(b))
+}
+
+fn status_rank(status: TaskStatus) -> u8 {
+    match status {
+        TaskStatus::Todo => 0,
+        TaskStatus::InProgress => 1,
+        TaskStatus::InReview => 2,
+        TaskStatus::Blocked => 3,
+        TaskStatus::Done => 4,
+        TaskStatus::Cancelled => 5,
+    }
+}
+
+/// Time estimates come before story points, each ordered by size
+fn estimate_rank(estimate: Estimate) -> (u8, u32) {
+    match estimate {
+        Estimate::Time { minutes } => (0, minutes),
+        Estimate::Points { points } => (1, points),
+    }
+}
@@ -0,0 +1,82 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::task::{Estimate, EstimateTotal, Task, TaskId, TaskStatus};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Sprint {
+    pub name: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub capacity: Option<Estimate>,
+    #[serde(default)]
+    pub committed: Vec<TaskId>,
+    #[serde(default)]
+    pub closed: bool,
+}
+
+impl Sprint {
+    pub fn new(name: String, start: NaiveDate, end: NaiveDate, capacity: Option<Estimate>) -> Self {
+        Sprint {
+            name,
+            start,
+            end,
+            capacity,
+            committed: vec![],
+            closed: false,
+        }
+    }
+
+    pub fn is_active(&self, today: NaiveDate) -> bool {
+        !self.closed && self.start <= today && today <= self.end
+    }
+
+    pub fn commit(&mut self, id: TaskId) {
+        if !self.committed.contains(&id) {
+            self.committed.push(id);
+        }
+    }
+
+    pub fn uncommit(&mut self, id: &TaskId) {
+        self.committed.retain(|committed| committed != id);
+    }
+}
+
+pub fn total_estimate<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> EstimateTotal {
+    let mut total = EstimateTotal::default();
+    tasks
+        .into_iter()
+        .filter_map(|task| task.estimate)
+        .for_each(|estimate| total.add(&estimate));
+    total
+}
+
+pub struct SprintPlan {
+    pub sprint: Sprint,
+    pub planned: Vec<TaskId>,
+    pub load: EstimateTotal,
+}
+
+impl SprintPlan {
+    pub fn is_over_capacity(&self) -> bool {
+        self.sprint
+            .capacity
+            .is_some_and(|capacity| self.load.exceeds(&capacity))
+    }
+}
+
+/// Tasks of a sprint grouped by status, in workflow order
+pub struct SprintBoard<'a> {
+    pub sprint: Sprint,
+    pub columns: Vec<(TaskStatus, Vec<&'a Task>)>,
+}
+
+pub struct SprintCloseReport {
+    pub sprint: Sprint,
+    pub committed: usize,
+    pub completed: usize,
+    pub committed_estimate: EstimateTotal,
+    pub completed_estimate: EstimateTotal,
+    pub carried_over: Vec<TaskId>,
+    pub next_sprint: Option<String>,
+}
@@ -0,0 +1,72 @@
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+use crate::{
+    dependency::blocked_by,
+    task::{Task, TaskId, TaskStatus},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum StandupFormat {
+    #[default]
+    Markdown,
+    Slack,
+    Plain,
+}
+
+/// The last weekday before `today`
+pub fn previous_working_day(today: NaiveDate) -> NaiveDate {
+    let days_back = match today.weekday() {
+        Weekday::Mon => 3,
+        Weekday::Sun => 2,
+        _ => 1,
+    };
+    today - Days::new(days_back)
+}
+
+pub struct BlockedTask<'a> {
+    pub task: &'a Task,
+    pub blocked_by: Vec<TaskId>,
+}
+
+pub struct Standup<'a> {
+    pub today: NaiveDate,
+    pub since: NaiveDate,
+    pub done: Vec<&'a Task>,
+    pub in_progress: Vec<&'a Task>,
+    pub blocked: Vec<BlockedTask<'a>>,
+}
+
+/// Blocked tasks are those with the blocked status or waiting on unfinished
+/// dependencies. They are not repeated under in progress
+pub fn build_standup(tasks: &[Task], since: NaiveDate, today: NaiveDate) -> Standup<'_> {
+    let done = tasks
+        .iter()
+        .filter(|task| {
+            task.completed_at()
+                .is_some_and(|completed_at| completed_at.date_naive() >= since)
+        })
+        .collect();
+    let blocked: Vec<BlockedTask> = tasks
+        .iter()
+        .filter(|task| !task.status.is_closed())
+        .map(|task| BlockedTask {
+            task,
+            blocked_by: blocked_by(task, tasks),
+        })
+        .filter(|blocked| {
+            blocked.task.status == TaskStatus::Blocked || !blocked.blocked_by.is_empty()
+        })
+        .collect();
+    let in_progress = tasks
+        .iter()
+        .filter(|task| task.status == TaskStatus::InProgress)
+        .filter(|task| !blocked.iter().any(|blocked| blocked.task.id == task.id))
+        .collect();
+    Standup {
+        today,
+        since,
+        done,
+        in_progress,
+        blocked,
+    }
+}
@@ -0,0 +1,135 @@
+use chrono::{DateTime, Days, Local, NaiveDate, TimeDelta};
+use serde::{Serialize, Serializer};
+
+use crate::{
+    datetime::week_bounds,
+    task::{Task, TaskStatus},
+};
+
+#[derive(Serialize)]
+pub struct StatusCount {
+    pub status: String,
+    pub count: usize,
+}
+
+#[derive(Serialize)]
+pub struct WeekStats {
+    pub week: NaiveDate,
+    pub created: usize,
+    pub completed: usize,
+}
+
+#[derive(Serialize)]
+pub struct OldestTask {
+    pub id: u32,
+    pub description: String,
+    #[serde(rename = "age_days", serialize_with = "serialize_days")]
+    pub age: TimeDelta,
+}
+
+#[derive(Serialize)]
+pub struct Stats {
+    pub total: usize,
+    pub by_status: Vec<StatusCount>,
+    pub weeks: Vec<WeekStats>,
+    #[serde(
+        rename = "average_open_age_days",
+        serialize_with = "serialize_optional_days"
+    )]
+    pub average_open_age: Option<TimeDelta>,
+    pub oldest_open_task: Option<OldestTask>,
+    #[serde(
+        rename = "average_lead_time_days",
+        serialize_with = "serialize_optional_days"
+    )]
+    pub average_lead_time: Option<TimeDelta>,
+}
+
+fn serialize_days<S: Serializer>(duration: &TimeDelta, serializer: S) -> Result<S::Ok, S::Error> {
+    let days = duration.num_minutes() as f64 / (24.0 * 60.0);
+    serializer.serialize_f64((days * 100.0).round() / 100.0)
+}
+
+fn serialize_optional_days<S: Serializer>(
+    duration: &Option<TimeDelta>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => serialize_days(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn average(durations: &[TimeDelta]) -> Option<TimeDelta> {
+    match durations.len() {
+        0 => None,
+        count => Some(durations.iter().sum::<TimeDelta>() / count as i32),
+    }
+}
+
+/// Statuses come in `statuses` order, followed by any other status in use.
+/// Created and completed counts cover the last `weeks` weeks, the current one included
+pub fn build_stats(
+    tasks: &[Task],
+    statuses: &[TaskStatus],
+    weeks: u32,
+    now: DateTime<Local>,
+) -> Stats {
+    let mut by_status: Vec<StatusCount> = statuses
+        .iter()
+        .map(|status| StatusCount {
+            status: status.to_string(),
+            count: 0,
+        })
+        .collect();
+    for task in tasks {
+        let status = task.status.to_string();
+        match by_status.iter_mut().find(|count| count.status == status) {
+            Some(count) => count.count += 1,
+            None => by_status.push(StatusCount { status, count: 1 }),
+        }
+    }
+
+    let (this_monday, _) = week_bounds(now.date_naive());
+    let weeks = (0..weeks)
+        .rev()
+        .map(|weeks_ago| {
+            let monday = this_monday - Days::new(7 * weeks_ago as u64);
+            let sunday = monday + Days::new(6);
+            let in_week = |at: DateTime<Local>| (monday..=sunday).contains(&at.date_naive());
+            WeekStats {
+                week: monday,
+                created: tasks.iter().filter(|task| in_week(task.created_at)).count(),
+                completed: tasks
+                    .iter()
+                    .filter_map(|task| task.completed_at())
+                    .filter(|completed_at| in_week(*completed_at))
+                    .count(),
+            }
+        })
+        .collect();
+
+    let open: Vec<&Task> = tasks
+        .iter()
+        .filter(|task| !task.status.is_closed())
+        .collect();
+    let ages: Vec<TimeDelta> = open.iter().map(|task| now - task.created_at).collect();
+    let oldest_open_task = open
+        .iter()
+        .min_by_key(|task| task.created_at)
+        .map(|task| OldestTask {
+            id: task.id.value(),
+            description: task.description.to_string(),
+            age: now - task.created_at,
+        });
+    let lead_times: Vec<TimeDelta> = tasks.iter().filter_map(|task| task.lead_time()).collect();
+
+    Stats {
+        total: tasks.len(),
+        by_status,
+        weeks,
+        average_open_age: average(&ages),
+        oldest_open_task,
+        average_lead_time: average(&lead_times),
+    }
+}
@@ -1,13 +1,26 @@
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDate, TimeDelta};
 use serde::{Deserialize, Serialize};
 
-use crate::{Error, Result};
+use crate::{
+    datetime::{format_duration, parse_duration},
+    recurrence::RecurrenceRule,
+    Error, Result,
+};
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum TaskStatus {
+    #[serde(alias = "todo")]
     Todo,
+    #[serde(alias = "in_progress")]
     InProgress,
+    #[serde(alias = "in_review")]
+    InReview,
+    #[serde(alias = "blocked")]
+    Blocked,
+    #[serde(alias = "done")]
     Done,
+    #[serde(alias = "cancelled")]
+    Cancelled,
 }
 
 impl std::fmt::Display for TaskStatus {
@@ -15,22 +28,168 @@ impl std::fmt::Display for TaskStatus {
         match self {
             TaskStatus::Todo => write!(f, "todo"),
             TaskStatus::InProgress => write!(f, "in_progress"),
+            TaskStatus::InReview => write!(f, "in_review"),
+            TaskStatus::Blocked => write!(f, "blocked"),
             TaskStatus::Done => write!(f, "done"),
+            TaskStatus::Cancelled => write!(f, "cancelled"),
         }
     }
 }
 
 impl TaskStatus {
     pub fn from_str(s: &str) -> Result<Self> {
-        match s {
+        match s.replace('-', "_").as_str() {
             "todo" => Ok(TaskStatus::Todo),
             "in_progress" => Ok(TaskStatus::InProgress),
+            "in_review" => Ok(TaskStatus::InReview),
+            "blocked" => Ok(TaskStatus::Blocked),
             "done" => Ok(TaskStatus::Done),
+            "cancelled" => Ok(TaskStatus::Cancelled),
             _ => Err(Error::UnknownStatus {
                 status: s.to_string(),
             }),
         }
     }
+
+    /// Closed tasks need no more work: they no longer block their dependents
+    pub fn is_closed(&self) -> bool {
+        matches!(self, TaskStatus::Done | TaskStatus::Cancelled)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatusChange {
+    pub from: TaskStatus,
+    pub to: TaskStatus,
+    pub at: DateTime<Local>,
+}
+
+/// A change to a field other than the status, with its new value. `None`
+/// when the field was cleared, and always for the body, which is too long to copy
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FieldChange {
+    pub at: DateTime<Local>,
+    pub field: String,
+    pub value: Option<String>,
+}
+
+/// A timestamped note, added with `rtask annotate`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Annotation {
+    pub at: DateTime<Local>,
+    pub text: String,
+}
+
+/// An item of a task's checklist, added with `rtask check add`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub done: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimeEntry {
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+}
+
+impl TimeEntry {
+    /// Running entries count up to now
+    pub fn duration(&self) -> TimeDelta {
+        self.end.unwrap_or_else(Local::now) - self.start
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub enum Estimate {
+    Time { minutes: u32 },
+    Points { points: u32 },
+}
+
+impl std::fmt::Display for Estimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Estimate::Time { minutes } => {
+                write!(
+                    f,
+                    "{}",
+                    format_duration(TimeDelta::minutes(*minutes as i64))
+                )
+            }
+            Estimate::Points { points } => write!(f, "{}pt", points),
+        }
+    }
+}
+
+/// Most story points a task can be estimated at
+const MAX_POINTS: u32 = 1000;
+
+impl Estimate {
+    /// Parses durations such as `3h` or `1h30m`, or story points such as `5pt`
+    pub fn from_str(s: &str) -> Result<Self> {
+        let points = s
+            .strip_suffix("pt")
+            .or_else(|| s.strip_suffix("sp"))
+            .map(|points| points.parse::<u32>());
+        match points {
+            Some(Ok(points)) if points <= MAX_POINTS => Ok(Estimate::Points { points }),
+            Some(_) => Err(Error::InvalidEstimate {
+                estimate: s.to_string(),
+            }),
+            None => {
+                let duration = parse_duration(s).map_err(|_| Error::InvalidEstimate {
+                    estimate: s.to_string(),
+                })?;
+                Ok(Estimate::Time {
+                    minutes: duration.num_minutes() as u32,
+                })
+            }
+        }
+    }
+}
+
+/// Sum of estimates, keeping durations and story points apart
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct EstimateTotal {
+    pub minutes: Option<u32>,
+    pub points: Option<u32>,
+}
+
+impl EstimateTotal {
+    pub fn add(&mut self, estimate: &Estimate) {
+        match estimate {
+            Estimate::Time { minutes } => {
+                self.minutes = Some(self.minutes.unwrap_or(0).saturating_add(*minutes))
+            }
+            Estimate::Points { points } => {
+                self.points = Some(self.points.unwrap_or(0).saturating_add(*points))
+            }
+        }
+    }
+
+    /// Whether this total goes beyond `capacity`, comparing like with like
+    pub fn exceeds(&self, capacity: &Estimate) -> bool {
+        match capacity {
+            Estimate::Time { minutes } => self.minutes.is_some_and(|total| total > *minutes),
+            Estimate::Points { points } => self.points.is_some_and(|total| total > *points),
+        }
+    }
+}
+
+impl std::fmt::Display for EstimateTotal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts: Vec<String> = vec![];
+        if let Some(minutes) = self.minutes {
+            parts.push(Estimate::Time { minutes }.to_string());
+        }
+        if let Some(points) = self.points {
+            parts.push(Estimate::Points { points }.to_string());
+        }
+        match parts.is_empty() {
+            true => write!(f, "-"),
+            false => write!(f, "{}", parts.join(" + ")),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -40,6 +199,33 @@ pub struct Task {
     pub status: TaskStatus,
     pub created_at: DateTime<Local>,
     pub updated_at: DateTime<Local>,
+    #[serde(default)]
+    pub depends_on: Vec<TaskId>,
+    #[serde(default)]
+    pub due: Option<NaiveDate>,
+    #[serde(default)]
+    pub series: Option<TaskId>,
+    #[serde(default)]
+    pub recur: Option<RecurrenceRule>,
+    #[serde(default)]
+    pub history: Vec<StatusChange>,
+    #[serde(default)]
+    pub time_entries: Vec<TimeEntry>,
+    #[serde(default)]
+    pub estimate: Option<Estimate>,
+    #[serde(default)]
+    pub milestone: Option<String>,
+    #[serde(default)]
+    pub sprint: Option<String>,
+    /// Longer details in Markdown, the description being the title
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    #[serde(default)]
+    pub changes: Vec<FieldChange>,
 }
 
 impl Task {
@@ -51,18 +237,234 @@ impl Task {
             status: TaskStatus::Todo,
             created_at: now,
             updated_at: now,
+            depends_on: vec![],
+            due: None,
+            series: None,
+            recur: None,
+            history: vec![],
+            time_entries: vec![],
+            estimate: None,
+            milestone: None,
+            sprint: None,
+            body: None,
+            annotations: vec![],
+            checklist: vec![],
+            changes: vec![],
         }
     }
 
+    pub fn annotate(&mut self, text: &str) -> Result<()> {
+        let text = clean_text(text, false);
+        if text.is_empty() {
+            return Err(Error::EmptyAnnotation);
+        }
+        let now = Local::now();
+        self.annotations.push(Annotation { at: now, text });
+        self.updated_at = now;
+        Ok(())
+    }
+
+    pub fn add_checklist_item(&mut self, text: &str) -> Result<()> {
+        let text = clean_text(text, false);
+        if text.is_empty() {
+            return Err(Error::EmptyChecklistItem);
+        }
+        self.checklist.push(ChecklistItem { text, done: false });
+        self.updated_at = Local::now();
+        Ok(())
+    }
+
+    /// Checks the item at `number`, counting from 1 as `show` lists them
+    pub fn check_item(&mut self, number: usize) -> Result<()> {
+        let item = number
+            .checked_sub(1)
+            .and_then(|position| self.checklist.get_mut(position))
+            .ok_or(Error::ChecklistItemNotFound {
+                id: self.id.to_string(),
+                number,
+            })?;
+        item.done = true;
+        self.updated_at = Local::now();
+        Ok(())
+    }
+
+    /// Checked and total items, `None` without a checklist
+    pub fn checklist_progress(&self) -> Option<(usize, usize)> {
+        if self.checklist.is_empty() {
+            return None;
+        }
+        let done = self.checklist.iter().filter(|item| item.done).count();
+        Some((done, self.checklist.len()))
+    }
+
+    /// Records a change of `field` when `from` and `to` differ
+    fn record_change(&mut self, field: &str, from: Option<String>, to: Option<String>) {
+        if from == to {
+            return;
+        }
+        let now = Local::now();
+        self.changes.push(FieldChange {
+            at: now,
+            field: field.to_string(),
+            value: to.filter(|_| field != "body"),
+        });
+        self.updated_at = now;
+    }
+
+    pub fn set_sprint(&mut self, sprint: Option<String>) {
+        self.record_change("sprint", self.sprint.clone(), sprint.clone());
+        self.sprint = sprint;
+    }
+
+    pub fn set_milestone(&mut self, milestone: Option<String>) {
+        self.record_change("milestone", self.milestone.clone(), milestone.clone());
+        self.milestone = milestone;
+    }
+
+    pub fn set_estimate(&mut self, estimate: Option<Estimate>) {
+        let from = self.estimate.map(|estimate| estimate.to_string());
+        let to = estimate.map(|estimate| estimate.to_string());
+        self.record_change("estimate", from, to);
+        self.estimate = estimate;
+    }
+
+    pub fn set_due(&mut self, due: Option<NaiveDate>) {
+        let to = due.map(|due| due.to_string());
+        self.record_change("due", self.due.map(|due| due.to_string()), to);
+        self.due = due;
+    }
+
+    pub fn set_body(&mut self, body: Option<String>) {
+        self.record_change("body", self.body.clone(), body.clone());
+        self.body = body;
+    }
+
+    pub fn set_dependencies(&mut self, depends_on: Vec<TaskId>) {
+        let ids = |ids: &[TaskId]| {
+            let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            Some(ids.join(", ")).filter(|ids| !ids.is_empty())
+        };
+        self.record_change("depends_on", ids(&self.depends_on), ids(&depends_on));
+        self.depends_on = depends_on;
+    }
+
+    /// Estimate left for open tasks: time estimates are reduced by the tracked time
+    pub fn remaining_estimate(&self) -> Option<Estimate> {
+        if self.status.is_closed() {
+            return None;
+        }
+        match self.estimate? {
+            Estimate::Time { minutes } => {
+                let tracked = self.tracked_time().num_minutes().max(0) as u32;
+                Some(Estimate::Time {
+                    minutes: minutes.saturating_sub(tracked),
+                })
+            }
+            points => Some(points),
+        }
+    }
+
+    pub fn set_recurrence(&mut self, recur: Option<RecurrenceRule>) {
+        let to = recur.map(|recur| recur.to_string());
+        self.record_change("recur", self.recur.map(|recur| recur.to_string()), to);
+        self.recur = recur;
+    }
+
     pub fn set_description(&mut self, description: TaskDescription) {
+        let (from, to) = (self.description.to_string(), description.to_string());
+        self.record_change("description", Some(from), Some(to));
         self.description = description;
-        self.updated_at = Local::now();
     }
 
     pub fn set_status(&mut self, status: TaskStatus) {
+        let now = Local::now();
+        self.history.push(StatusChange {
+            from: self.status,
+            to: status,
+            at: now,
+        });
         self.status = status;
+        self.updated_at = now;
+    }
+
+    pub fn running_time_entry(&self) -> Option<&TimeEntry> {
+        self.time_entries.iter().find(|entry| entry.end.is_none())
+    }
+
+    pub fn start_timer(&mut self) {
+        let now = Local::now();
+        self.time_entries.push(TimeEntry {
+            start: now,
+            end: None,
+        });
+        self.updated_at = now;
+    }
+
+    /// Returns the duration of the stopped entry, if a timer was running
+    pub fn stop_timer(&mut self) -> Option<TimeDelta> {
+        let now = Local::now();
+        let entry = self
+            .time_entries
+            .iter_mut()
+            .find(|entry| entry.end.is_none())?;
+        entry.end = Some(now);
+        self.updated_at = now;
+        Some(now - entry.start)
+    }
+
+    pub fn log_time(&mut self, start: DateTime<Local>, duration: TimeDelta) {
+        self.time_entries.push(TimeEntry {
+            start,
+            end: Some(start + duration),
+        });
         self.updated_at = Local::now();
     }
+
+    pub fn tracked_time(&self) -> TimeDelta {
+        self.time_entries.iter().map(|entry| entry.duration()).sum()
+    }
+
+    /// When the task was last marked as done, if it still is.
+    /// Tasks completed before history was recorded have no completion time.
+    pub fn completed_at(&self) -> Option<DateTime<Local>> {
+        if self.status != TaskStatus::Done {
+            return None;
+        }
+        self.history
+            .iter()
+            .rev()
+            .find(|change| change.to == TaskStatus::Done)
+            .map(|change| change.at)
+    }
+
+    pub fn started_at(&self) -> Option<DateTime<Local>> {
+        self.history
+            .iter()
+            .find(|change| change.to == TaskStatus::InProgress)
+            .map(|change| change.at)
+    }
+
+    /// Time from creation to completion
+    pub fn lead_time(&self) -> Option<TimeDelta> {
+        self.completed_at()
+            .map(|completed_at| completed_at - self.created_at)
+    }
+
+    /// Time from first being in progress to completion
+    pub fn cycle_time(&self) -> Option<TimeDelta> {
+        match (self.started_at(), self.completed_at()) {
+            (Some(started_at), Some(completed_at)) => Some(completed_at - started_at),
+            _ => None,
+        }
+    }
+
+    pub fn add_dependency(&mut self, id: TaskId) {
+        if !self.depends_on.contains(&id) {
+            let mut depends_on = self.depends_on.clone();
+            depends_on.push(id);
+            self.set_dependencies(depends_on);
+        }
+    }
 }
 
 impl std::fmt::Display for Task {
@@ -76,11 +478,46 @@ Created At: {}
 Updated At: {}
 "#,
             self.id, self.description, self.status, self.created_at, self.updated_at
-        )
+        )?;
+        if !self.depends_on.is_empty() {
+            let ids: Vec<String> = self.depends_on.iter().map(|id| id.to_string()).collect();
+            writeln!(f, "Depends On: {}", ids.join(", "))?;
+        }
+        if let Some(due) = self.due {
+            writeln!(f, "Due: {}", due)?;
+        }
+        if let Some(recur) = self.recur {
+            writeln!(f, "Repeats: {}", recur)?;
+        }
+        if let Some(milestone) = &self.milestone {
+            writeln!(f, "Milestone: {}", milestone)?;
+        }
+        if let Some(sprint) = &self.sprint {
+            writeln!(f, "Sprint: {}", sprint)?;
+        }
+        if let Some(estimate) = self.estimate {
+            writeln!(f, "Estimate: {}", estimate)?;
+        }
+        if let Some((done, total)) = self.checklist_progress() {
+            writeln!(f, "Checklist: [{}/{}]", done, total)?;
+        }
+        if !self.time_entries.is_empty() {
+            let running = match self.running_time_entry() {
+                Some(_) => " (timer running)",
+                None => "",
+            };
+            writeln!(
+                f,
+                "Tracked: {}{}",
+                format_duration(self.tracked_time()),
+                running
+            )?;
+        }
+        Ok(())
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Copy, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone)]
 pub struct TaskId {
     id: u32,
 }
@@ -102,6 +539,10 @@ impl TaskId {
     pub fn new(id: u32) -> Result<Self> {
         Ok(Self { id })
     }
+
+    pub fn value(&self) -> u32 {
+        self.id
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -116,10 +557,36 @@ impl std::fmt::Display for TaskDescription {
 }
 
 impl TaskDescription {
+    /// Trimmed, and stripped of control characters such as line breaks
     pub fn new(description: String) -> Result<Self> {
+        let description = clean_text(&description, false);
         if description.is_empty() {
             return Err(Error::EmptyDescription);
         }
         Ok(Self { description })
     }
 }
+
+/// `None` when there is nothing but whitespace
+pub fn clean_body(body: &str) -> Option<String> {
+    Some(clean_text(body, true)).filter(|body| !body.is_empty())
+}
+
+/// Trims `text` and strips control characters, except for line breaks and
+/// tabs in multi-line text. In single-line text they become a single space
+fn clean_text(text: &str, multi_line: bool) -> String {
+    let mut cleaned = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' | '\t' if multi_line => cleaned.push(c),
+            '\n' | '\r' | '\t' if !multi_line => {
+                if !cleaned.ends_with(char::is_whitespace) {
+                    cleaned.push(' ');
+                }
+            }
+            c if c.is_control() => {}
+            c => cleaned.push(c),
+        }
+    }
+    cleaned.trim().to_string()
+}
@@ -0,0 +1,108 @@
+use chrono::{NaiveDate, TimeDelta};
+use serde::Deserialize;
+
+use crate::task::Task;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimesheetGrouping {
+    Day,
+    Task,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    #[default]
+    Nearest,
+    Up,
+    Down,
+}
+
+/// Rounding applied to each timesheet row. `minutes: 0` disables it
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RoundingConfig {
+    pub minutes: u32,
+    pub mode: RoundingMode,
+}
+
+impl RoundingConfig {
+    pub fn round(&self, duration: TimeDelta) -> TimeDelta {
+        if self.minutes == 0 {
+            return duration;
+        }
+        let step = self.minutes as i64;
+        let minutes = duration.num_minutes();
+        let rounded = match self.mode {
+            RoundingMode::Nearest => (minutes + step / 2) / step * step,
+            RoundingMode::Up => (minutes + step - 1) / step * step,
+            RoundingMode::Down => minutes / step * step,
+        };
+        TimeDelta::minutes(rounded)
+    }
+}
+
+pub struct TimesheetRow {
+    pub labels: Vec<String>,
+    pub duration: TimeDelta,
+}
+
+pub struct Timesheet {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub grouping: TimesheetGrouping,
+    pub rows: Vec<TimesheetRow>,
+    pub total: TimeDelta,
+}
+
+impl Timesheet {
+    pub fn headers(&self) -> Vec<&'static str> {
+        match self.grouping {
+            TimesheetGrouping::Day => vec!["Date"],
+            TimesheetGrouping::Task => vec!["Id", "Description"],
+        }
+    }
+}
+
+/// Sums the time entries started between `from` and `to` (both included)
+pub fn build_timesheet(
+    tasks: &[Task],
+    from: NaiveDate,
+    to: NaiveDate,
+    grouping: TimesheetGrouping,
+    rounding: &RoundingConfig,
+) -> Timesheet {
+    let mut rows: Vec<TimesheetRow> = vec![];
+    for task in tasks {
+        for entry in &task.time_entries {
+            let day = entry.start.date_naive();
+            if day < from || day > to {
+                continue;
+            }
+            let labels = match grouping {
+                TimesheetGrouping::Day => vec![day.to_string()],
+                TimesheetGrouping::Task => vec![task.id.to_string(), task.description.to_string()],
+            };
+            match rows.iter_mut().find(|row| row.labels == labels) {
+                Some(row) => row.duration += entry.duration(),
+                None => rows.push(TimesheetRow {
+                    labels,
+                    duration: entry.duration(),
+                }),
+            }
+        }
+    }
+    if grouping == TimesheetGrouping::Day {
+        rows.sort_by(|a, b| a.labels.cmp(&b.labels));
+    }
+    rows.iter_mut()
+        .for_each(|row| row.duration = rounding.round(row.duration));
+    let total = rows.iter().map(|row| row.duration).sum();
+    Timesheet {
+        from,
+        to,
+        grouping,
+        rows,
+        total,
+    }
+}